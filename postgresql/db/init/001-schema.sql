@@ -78,9 +78,11 @@ CREATE TABLE IF NOT EXISTS document (
 	filename TEXT,
 	author TEXT,
 	title TEXT,
-	doc_metadata JSONB
+	doc_metadata JSONB  -- {"source_sha256": ..., "chunking": ...}
 );
 
+CREATE INDEX IF NOT EXISTS idx_document_source_sha256 ON document ((doc_metadata->>'source_sha256'));
+
 -- Page
 CREATE TABLE IF NOT EXISTS page (
 	id BIGSERIAL PRIMARY KEY,
@@ -114,10 +116,37 @@ CREATE TABLE IF NOT EXISTS image_chunk (
 	parent_page BIGINT REFERENCES page(id),
 	contents BYTEA NOT NULL,
 	mimetype VARCHAR(255) NOT NULL,
+	chunk_metadata JSONB,  -- {"kind": "page" | "region", "bbox": ...}
 	embedding VECTOR(768),
 	embeddings VECTOR(768)[]  -- Multi-vector for ColPali style image retrieval
 );
 
+-- ImageChunkThumbnail
+-- Downscaled previews of an image chunk, size is the bounding square in pixels
+CREATE TABLE IF NOT EXISTS image_chunk_thumbnail (
+	image_chunk_id BIGINT NOT NULL REFERENCES image_chunk(id),
+	size INT NOT NULL,
+	contents BYTEA NOT NULL,
+	mimetype VARCHAR(255) NOT NULL,
+	PRIMARY KEY (image_chunk_id, size)
+);
+
+-- SourceBlob
+-- Content-addressed copies of source files, so documents can be opened on other machines
+CREATE TABLE IF NOT EXISTS source_blob (
+	sha256 VARCHAR(64) PRIMARY KEY,
+	byte_size BIGINT NOT NULL
+);
+
+-- SourceBlobChunk
+-- Contents of a source blob, split into rows of at most 1 MiB in seq order
+CREATE TABLE IF NOT EXISTS source_blob_chunk (
+	sha256 VARCHAR(64) NOT NULL REFERENCES source_blob(sha256),
+	seq INT NOT NULL,
+	data BYTEA NOT NULL,
+	PRIMARY KEY (sha256, seq)
+);
+
 -- PageChunkRelation
 CREATE TABLE IF NOT EXISTS page_chunk_relation (
 	page_id BIGINT NOT NULL REFERENCES page(id),
@@ -1,6 +1,8 @@
 use tauri::State;
 
-use crate::db::{create_pool, test_connection as test_db_connection, DatabaseConfig};
+use crate::db::{
+    create_pool, ensure_schema, test_connection as test_db_connection, DatabaseConfig,
+};
 use crate::error::{AppError, Result};
 use crate::state::AppState;
 
@@ -8,6 +10,7 @@ use crate::state::AppState;
 pub async fn connect_database(config: DatabaseConfig, state: State<'_, AppState>) -> Result<bool> {
     let pool = create_pool(&config).await?;
     test_db_connection(&pool).await?;
+    ensure_schema(&pool).await?;
     state.set_connection(pool).await;
     Ok(true)
 }
@@ -1,10 +1,17 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use sqlx::{PgConnection, PgPool};
 use tauri::State;
+use tokio::task::spawn_blocking;
 
 use crate::db::{
-    Document, DocumentDeletionCheck, DocumentWithPages, File, FileWithDocuments, ImageChunkInfo,
-    PageInfo, PageWithChunks, Query,
+    store_source, Chunk, Document, DocumentDeletionCheck, DocumentWithPages, File,
+    FileWithDocuments, FingerprintedPage, ImageChunkInfo, NearDuplicatePages, PageInfo,
+    PageWithChunks, Query, RelinkReport, SourceProblem, SourceStoreReport,
 };
 use crate::error::{AppError, Result};
+use crate::ingest::{combined_sha256, file_sha256, near_duplicate_pairs, parse_dhash};
 use crate::state::AppState;
 
 #[tauri::command]
@@ -101,7 +108,8 @@ pub async fn get_document_with_pages(
     // Fetch pages without image_contents (too large for list response)
     let pages = sqlx::query_as::<_, PageInfo>(
         r#"
-        SELECT id, page_num, document_id, mimetype, page_metadata
+        SELECT id, page_num, document_id, mimetype, page_metadata, page_metadata->'image' AS image,
+               page_metadata->>'page_label' AS page_label
         FROM page
         WHERE document_id = $1
         ORDER BY page_num ASC
@@ -116,7 +124,7 @@ pub async fn get_document_with_pages(
         // Fetch chunks without binary contents
         let chunks = sqlx::query_as::<_, ImageChunkInfo>(
             r#"
-            SELECT id, parent_page, mimetype
+            SELECT id, parent_page, mimetype, chunk_metadata, chunk_metadata->'image' AS image
             FROM image_chunk
             WHERE parent_page = $1
             ORDER BY id ASC
@@ -141,7 +149,8 @@ pub async fn get_pages(document_id: i64, state: State<'_, AppState>) -> Result<V
 
     let pages = sqlx::query_as::<_, PageInfo>(
         r#"
-        SELECT id, page_num, document_id, mimetype, page_metadata
+        SELECT id, page_num, document_id, mimetype, page_metadata, page_metadata->'image' AS image,
+               page_metadata->>'page_label' AS page_label
         FROM page
         WHERE document_id = $1
         ORDER BY page_num ASC
@@ -163,7 +172,7 @@ pub async fn get_page_chunks(
 
     let chunks = sqlx::query_as::<_, ImageChunkInfo>(
         r#"
-        SELECT id, parent_page, mimetype
+        SELECT id, parent_page, mimetype, chunk_metadata, chunk_metadata->'image' AS image
         FROM image_chunk
         WHERE parent_page = $1
         ORDER BY id ASC
@@ -176,6 +185,27 @@ pub async fn get_page_chunks(
     Ok(chunks)
 }
 
+/// Get the text chunks linked to a page (e.g. its extracted text layer)
+#[tauri::command]
+pub async fn get_page_text_chunks(page_id: i64, state: State<'_, AppState>) -> Result<Vec<Chunk>> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    let chunks = sqlx::query_as::<_, Chunk>(
+        r#"
+        SELECT c.id, c.contents, c.is_table, c.table_type
+        FROM chunk c
+        JOIN page_chunk_relation pcr ON pcr.chunk_id = c.id
+        WHERE pcr.page_id = $1
+        ORDER BY c.id ASC
+        "#,
+    )
+    .bind(page_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(chunks)
+}
+
 #[tauri::command]
 pub async fn get_file_path(document_id: i64, state: State<'_, AppState>) -> Result<String> {
     let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
@@ -233,7 +263,7 @@ pub async fn list_documents(state: State<'_, AppState>) -> Result<Vec<Document>>
 }
 
 /// Check whether a document can be safely deleted by looking for
-/// queries that reference its chunks as retrieval ground truth evidence.
+/// queries that reference its image or text chunks as retrieval ground truth evidence.
 #[tauri::command]
 pub async fn check_document_deletable(
     document_id: i64,
@@ -246,9 +276,11 @@ pub async fn check_document_deletable(
         SELECT DISTINCT q.id, q.contents, q.query_to_llm, q.generation_gt
         FROM query q
         JOIN retrieval_relation rr ON rr.query_id = q.id
-        JOIN image_chunk ic ON ic.id = rr.image_chunk_id
-        JOIN page p ON p.id = ic.parent_page
-        WHERE p.document_id = $1
+        LEFT JOIN image_chunk ic ON ic.id = rr.image_chunk_id
+        LEFT JOIN page p ON p.id = ic.parent_page
+        LEFT JOIN page_chunk_relation pcr ON pcr.chunk_id = rr.chunk_id
+        LEFT JOIN page tp ON tp.id = pcr.page_id
+        WHERE p.document_id = $1 OR tp.document_id = $1
         ORDER BY q.id
         "#,
     )
@@ -273,9 +305,11 @@ pub async fn delete_document(document_id: i64, state: State<'_, AppState>) -> Re
         r#"
         SELECT COUNT(*)
         FROM retrieval_relation rr
-        JOIN image_chunk ic ON ic.id = rr.image_chunk_id
-        JOIN page p ON p.id = ic.parent_page
-        WHERE p.document_id = $1
+        LEFT JOIN image_chunk ic ON ic.id = rr.image_chunk_id
+        LEFT JOIN page p ON p.id = ic.parent_page
+        LEFT JOIN page_chunk_relation pcr ON pcr.chunk_id = rr.chunk_id
+        LEFT JOIN page tp ON tp.id = pcr.page_id
+        WHERE p.document_id = $1 OR tp.document_id = $1
         "#,
     )
     .bind(document_id)
@@ -327,17 +361,64 @@ pub async fn delete_document(document_id: i64, state: State<'_, AppState>) -> Re
         .execute(&mut *tx)
         .await?;
 
+        // Delete thumbnails of those chunks
+        sqlx::query(
+            r#"
+            DELETE FROM image_chunk_thumbnail
+            WHERE image_chunk_id IN (
+                SELECT id FROM image_chunk WHERE parent_page = ANY($1)
+            )
+            "#,
+        )
+        .bind(&page_ids)
+        .execute(&mut *tx)
+        .await?;
+
         // Delete image_chunk rows
         sqlx::query(r#"DELETE FROM image_chunk WHERE parent_page = ANY($1)"#)
             .bind(&page_ids)
             .execute(&mut *tx)
             .await?;
 
+        // Collect text chunks linked to this document's pages
+        let chunk_ids: Vec<i64> = sqlx::query_scalar(
+            r#"SELECT DISTINCT chunk_id FROM page_chunk_relation WHERE page_id = ANY($1)"#,
+        )
+        .bind(&page_ids)
+        .fetch_all(&mut *tx)
+        .await?;
+
         // Delete page_chunk_relation rows
         sqlx::query(r#"DELETE FROM page_chunk_relation WHERE page_id = ANY($1)"#)
             .bind(&page_ids)
             .execute(&mut *tx)
             .await?;
+
+        if !chunk_ids.is_empty() {
+            // Delete chunk_retrieved_result for text chunks no longer linked to any page
+            sqlx::query(
+                r#"
+                DELETE FROM chunk_retrieved_result
+                WHERE chunk_id = ANY($1)
+                  AND chunk_id NOT IN (SELECT chunk_id FROM page_chunk_relation)
+                "#,
+            )
+            .bind(&chunk_ids)
+            .execute(&mut *tx)
+            .await?;
+
+            // Delete the orphaned text chunks
+            sqlx::query(
+                r#"
+                DELETE FROM chunk
+                WHERE id = ANY($1)
+                  AND id NOT IN (SELECT chunk_id FROM page_chunk_relation)
+                "#,
+            )
+            .bind(&chunk_ids)
+            .execute(&mut *tx)
+            .await?;
+        }
     }
 
     // Delete pages
@@ -373,3 +454,624 @@ pub async fn delete_document(document_id: i64, state: State<'_, AppState>) -> Re
 
     Ok(true)
 }
+
+/// Delete a page of an image document together with its image, region and
+/// text chunks. Later pages move up one place, so page numbers stay
+/// contiguous. Fails if any query references the page's chunks as evidence,
+/// or if it is the document's last page; delete the document instead.
+#[tauri::command]
+pub async fn delete_page(page_id: i64, state: State<'_, AppState>) -> Result<bool> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    let document_id: i64 = sqlx::query_scalar(r#"SELECT document_id FROM page WHERE id = $1"#)
+        .bind(page_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Page {} not found", page_id)))?;
+    ensure_image_document(&pool, document_id).await?;
+
+    let page_count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM page WHERE document_id = $1"#)
+        .bind(document_id)
+        .fetch_one(&pool)
+        .await?;
+    if page_count <= 1 {
+        return Err(AppError::Custom(format!(
+            "Page {} is the only page of document {}; delete the document instead",
+            page_id, document_id
+        )));
+    }
+
+    let blocking_count: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*)
+        FROM retrieval_relation rr
+        LEFT JOIN image_chunk ic ON ic.id = rr.image_chunk_id
+        LEFT JOIN page_chunk_relation pcr ON pcr.chunk_id = rr.chunk_id
+        WHERE ic.parent_page = $1 OR pcr.page_id = $1
+        "#,
+    )
+    .bind(page_id)
+    .fetch_one(&pool)
+    .await?;
+
+    if blocking_count > 0 {
+        return Err(AppError::Custom(format!(
+            "Cannot delete page {}: {} retrieval relation(s) still reference its chunks",
+            page_id, blocking_count
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM image_chunk_retrieved_result
+        WHERE image_chunk_id IN (SELECT id FROM image_chunk WHERE parent_page = $1)
+        "#,
+    )
+    .bind(page_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM image_chunk_thumbnail
+        WHERE image_chunk_id IN (SELECT id FROM image_chunk WHERE parent_page = $1)
+        "#,
+    )
+    .bind(page_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(r#"DELETE FROM image_chunk WHERE parent_page = $1"#)
+        .bind(page_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let chunk_ids: Vec<i64> = sqlx::query_scalar(
+        r#"DELETE FROM page_chunk_relation WHERE page_id = $1 RETURNING chunk_id"#,
+    )
+    .bind(page_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    if !chunk_ids.is_empty() {
+        // Text chunks shared with other pages stay
+        sqlx::query(
+            r#"
+            DELETE FROM chunk_retrieved_result
+            WHERE chunk_id = ANY($1)
+              AND chunk_id NOT IN (SELECT chunk_id FROM page_chunk_relation)
+            "#,
+        )
+        .bind(&chunk_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM chunk
+            WHERE id = ANY($1)
+              AND id NOT IN (SELECT chunk_id FROM page_chunk_relation)
+            "#,
+        )
+        .bind(&chunk_ids)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    sqlx::query(r#"DELETE FROM page WHERE id = $1"#)
+        .bind(page_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let remaining: Vec<i64> =
+        sqlx::query_scalar(r#"SELECT id FROM page WHERE document_id = $1 ORDER BY page_num"#)
+            .bind(document_id)
+            .fetch_all(&mut *tx)
+            .await?;
+    renumber_pages(&mut tx, document_id, &remaining).await?;
+    refresh_image_document_hash(&mut tx, document_id).await?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// Reorder the pages of an image document. `page_ids` lists every page of
+/// the document once, in the new order; pages are renumbered from 1.
+/// Page and chunk ids do not change, so evidence is unaffected.
+#[tauri::command]
+pub async fn reorder_pages(
+    document_id: i64,
+    page_ids: Vec<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<PageInfo>> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    ensure_image_document(&pool, document_id).await?;
+
+    let mut current: Vec<i64> = sqlx::query_scalar(r#"SELECT id FROM page WHERE document_id = $1"#)
+        .bind(document_id)
+        .fetch_all(&pool)
+        .await?;
+    let mut requested = page_ids.clone();
+    current.sort_unstable();
+    requested.sort_unstable();
+    if current != requested {
+        return Err(AppError::Custom(format!(
+            "The new page order must list each of the {} pages of document {} exactly once",
+            current.len(),
+            document_id
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    renumber_pages(&mut tx, document_id, &page_ids).await?;
+    refresh_image_document_hash(&mut tx, document_id).await?;
+
+    let pages = sqlx::query_as::<_, PageInfo>(
+        r#"
+        SELECT id, page_num, document_id, mimetype, page_metadata, page_metadata->'image' AS image,
+               page_metadata->>'page_label' AS page_label
+        FROM page
+        WHERE document_id = $1
+        ORDER BY page_num ASC
+        "#,
+    )
+    .bind(document_id)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(pages)
+}
+
+/// Fail unless `document_id` is an image document. Documents imported from
+/// a PDF or office file mirror their source, so their pages are not edited.
+pub(crate) async fn ensure_image_document(pool: &PgPool, document_id: i64) -> Result<()> {
+    let file_id: Option<i64> = sqlx::query_scalar(r#"SELECT path FROM document WHERE id = $1"#)
+        .bind(document_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Document {} not found", document_id)))?;
+
+    if file_id.is_some() {
+        return Err(AppError::Custom(format!(
+            "Document {} was imported from a PDF or office file; only image documents can be edited page by page",
+            document_id
+        )));
+    }
+    Ok(())
+}
+
+/// Number the pages of a document from 1 in the order of `page_ids`.
+/// Pages first move to negative numbers, so no intermediate state
+/// violates `uq_page_per_doc`.
+async fn renumber_pages(conn: &mut PgConnection, document_id: i64, page_ids: &[i64]) -> Result<()> {
+    sqlx::query(r#"UPDATE page SET page_num = -page_num WHERE document_id = $1"#)
+        .bind(document_id)
+        .execute(&mut *conn)
+        .await?;
+
+    sqlx::query(
+        r#"
+        UPDATE page p
+        SET page_num = o.position::int
+        FROM unnest($2::bigint[]) WITH ORDINALITY AS o(id, position)
+        WHERE p.id = o.id AND p.document_id = $1
+        "#,
+    )
+    .bind(document_id)
+    .bind(page_ids)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Recompute `doc_metadata.source_sha256` of an image document from the
+/// source files of its pages in page order, as `ingest_images` does, so
+/// duplicate detection matches the edited document
+pub(crate) async fn refresh_image_document_hash(
+    conn: &mut PgConnection,
+    document_id: i64,
+) -> Result<()> {
+    let mut file_hashes: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT page_metadata->>'source_sha256'
+        FROM page
+        WHERE document_id = $1 AND page_metadata ? 'source_sha256'
+        ORDER BY page_num
+        "#,
+    )
+    .bind(document_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    // Pages of a multi-page file share its hash
+    file_hashes.dedup();
+
+    sqlx::query(
+        r#"UPDATE document
+           SET doc_metadata = COALESCE(doc_metadata, '{}'::jsonb) || jsonb_build_object('source_sha256', $2::text)
+           WHERE id = $1"#,
+    )
+    .bind(document_id)
+    .bind(combined_sha256(&file_hashes))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+/// Copy the source files of existing documents into the content store, so
+/// they can be opened on machines without the original files; all documents
+/// when `document_id` is None. Sources are identified by the hashes recorded
+/// at import, so files that went missing or changed since are reported
+/// instead of stored. Documents imported before hashes were recorded are
+/// left out.
+#[tauri::command]
+pub async fn store_document_sources(
+    document_id: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<SourceStoreReport> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    // Pages of one source share its path and hash
+    let mut sources: Vec<(String, String)> = document_sources(&pool, document_id)
+        .await?
+        .into_iter()
+        .filter_map(|source| Some((source.path, source.sha256?)))
+        .collect();
+    sources.sort();
+    sources.dedup();
+
+    let mut report = SourceStoreReport {
+        stored: 0,
+        already_stored: 0,
+        missing: Vec::new(),
+        changed: Vec::new(),
+        too_large: Vec::new(),
+    };
+
+    let mut conn = pool.acquire().await?;
+    for (path, sha256) in sources {
+        match store_source(&mut conn, Path::new(&path), &sha256).await {
+            Ok(true) => report.stored += 1,
+            Ok(false) => report.already_stored += 1,
+            Err(AppError::SourceChanged(_)) => report.changed.push(path),
+            Err(AppError::SourceTooLarge(_)) => report.too_large.push(path),
+            Err(AppError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => {
+                report.missing.push(path)
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(report)
+}
+
+/// Longest path `file.path` can hold
+const MAX_SOURCE_PATH_LENGTH: i32 = 255;
+
+/// Point sources under `old_prefix` to `new_prefix` after the files were
+/// moved, in `file.path` and in `page_metadata.source_path`. Only whole
+/// path components match, so "/data/corpus" does not relink
+/// "/data/corpus2". Paths that would grow past `MAX_SOURCE_PATH_LENGTH`
+/// are left unchanged in both places and reported. Run `verify_sources`
+/// afterwards to check the new paths.
+#[tauri::command]
+pub async fn relink_sources(
+    old_prefix: String,
+    new_prefix: String,
+    state: State<'_, AppState>,
+) -> Result<RelinkReport> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    let old_prefix = old_prefix.trim_end_matches(['/', '\\']);
+    let new_prefix = new_prefix.trim_end_matches(['/', '\\']);
+    if old_prefix.is_empty() {
+        return Err(AppError::Custom(
+            "The path prefix to replace must not be empty".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    let too_long: Vec<String> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT path
+        FROM (
+            SELECT path FROM file
+            UNION ALL
+            SELECT page_metadata->>'source_path' FROM page WHERE page_metadata ? 'source_path'
+        ) sources
+        WHERE left(path, length($1)) = $1
+          AND substr(path, length($1) + 1, 1) IN ('', '/', '\')
+          AND length($2 || substr(path, length($1) + 1)) > $3
+        ORDER BY path
+        "#,
+    )
+    .bind(old_prefix)
+    .bind(new_prefix)
+    .bind(MAX_SOURCE_PATH_LENGTH)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let files = sqlx::query(
+        r#"
+        UPDATE file
+        SET path = $2 || substr(path, length($1) + 1)
+        WHERE left(path, length($1)) = $1
+          AND substr(path, length($1) + 1, 1) IN ('', '/', '\')
+          AND length($2 || substr(path, length($1) + 1)) <= $3
+        "#,
+    )
+    .bind(old_prefix)
+    .bind(new_prefix)
+    .bind(MAX_SOURCE_PATH_LENGTH)
+    .execute(&mut *tx)
+    .await?;
+
+    let pages = sqlx::query(
+        r#"
+        UPDATE page
+        SET page_metadata = jsonb_set(
+            page_metadata,
+            '{source_path}',
+            to_jsonb($2 || substr(page_metadata->>'source_path', length($1) + 1))
+        )
+        WHERE left(page_metadata->>'source_path', length($1)) = $1
+          AND substr(page_metadata->>'source_path', length($1) + 1, 1) IN ('', '/', '\')
+          AND length($2 || substr(page_metadata->>'source_path', length($1) + 1)) <= $3
+        "#,
+    )
+    .bind(old_prefix)
+    .bind(new_prefix)
+    .bind(MAX_SOURCE_PATH_LENGTH)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(RelinkReport {
+        files: files.rows_affected() as i64,
+        pages: pages.rows_affected() as i64,
+        too_long,
+    })
+}
+
+/// Check that the source files of documents still exist and match the
+/// hashes recorded at import; all documents when `document_id` is None.
+/// Returns the sources that are missing, unreadable or changed, with
+/// whether the content store holds a copy. Sources without a recorded hash are only
+/// checked for existence.
+#[tauri::command]
+pub async fn verify_sources(
+    document_id: Option<i64>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SourceProblem>> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    let mut sources = document_sources(&pool, document_id).await?;
+    sources.dedup_by(|a, b| a.document_id == b.document_id && a.path == b.path);
+
+    // Hash each file once, even when several documents share it
+    let mut paths: Vec<String> = sources.iter().map(|source| source.path.clone()).collect();
+    paths.sort();
+    paths.dedup();
+    let current_hashes: HashMap<String, Result<String>> = spawn_blocking(move || {
+        paths
+            .into_iter()
+            .map(|path| {
+                let hash = file_sha256(Path::new(&path));
+                (path, hash)
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Task join error: {}", e)))?;
+
+    let stored: Vec<String> = sqlx::query_scalar(r#"SELECT sha256 FROM source_blob"#)
+        .fetch_all(&pool)
+        .await?;
+
+    let mut problems = Vec::new();
+    for source in sources {
+        let (status, error) = match (&current_hashes[&source.path], &source.sha256) {
+            (Err(AppError::Io(e)), _) if e.kind() == std::io::ErrorKind::NotFound => {
+                ("missing", None)
+            }
+            (Err(e), _) => ("unreadable", Some(e.to_string())),
+            (Ok(current), Some(recorded)) if current != recorded => ("changed", None),
+            _ => continue,
+        };
+        problems.push(SourceProblem {
+            document_id: source.document_id,
+            title: source.title,
+            in_content_store: source
+                .sha256
+                .as_ref()
+                .is_some_and(|sha| stored.contains(sha)),
+            path: source.path,
+            status: status.to_string(),
+            error,
+        });
+    }
+
+    Ok(problems)
+}
+
+/// A source file of a document, with the hash recorded when it was imported
+struct DocumentSource {
+    document_id: i64,
+    title: Option<String>,
+    path: String,
+    sha256: Option<String>,
+}
+
+/// Source files of documents in document order: the file of each PDF or
+/// office document, and the file of each page of an image document
+async fn document_sources(pool: &PgPool, document_id: Option<i64>) -> Result<Vec<DocumentSource>> {
+    let rows: Vec<(i64, Option<String>, String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT document_id, title, path, sha256
+        FROM (
+            SELECT d.id AS document_id, d.title, f.path,
+                   d.doc_metadata->>'source_sha256' AS sha256, 0 AS page_num
+            FROM document d
+            JOIN file f ON d.path = f.id
+            WHERE $1::bigint IS NULL OR d.id = $1
+            UNION ALL
+            SELECT d.id, d.title, p.page_metadata->>'source_path',
+                   p.page_metadata->>'source_sha256', p.page_num
+            FROM page p
+            JOIN document d ON p.document_id = d.id
+            WHERE d.path IS NULL
+              AND p.page_metadata ? 'source_path'
+              AND ($1::bigint IS NULL OR d.id = $1)
+        ) sources
+        ORDER BY document_id, page_num
+        "#,
+    )
+    .bind(document_id)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(document_id, title, path, sha256)| DocumentSource {
+            document_id,
+            title,
+            path,
+            sha256,
+        })
+        .collect())
+}
+
+/// Hamming distance used when `find_near_duplicate_pages` is not given one
+const DEFAULT_NEAR_DUPLICATE_DISTANCE: u32 = 6;
+/// Beyond this many differing bits pages are no longer meaningfully similar
+const MAX_NEAR_DUPLICATE_DISTANCE: u32 = 16;
+/// Pairs returned by `find_near_duplicate_pages` when not given a limit
+const DEFAULT_NEAR_DUPLICATE_LIMIT: u32 = 500;
+
+/// Report pairs of near-duplicate pages across the whole database, judged by
+/// the perceptual hash in `page_metadata.fingerprint`, closest first and at
+/// most `limit` of them. Pages without a fingerprint and blank pages are
+/// left out. With `document_id`, only pairs involving a page of that
+/// document are reported, compared in the database.
+#[tauri::command]
+pub async fn find_near_duplicate_pages(
+    max_distance: Option<u32>,
+    document_id: Option<i64>,
+    limit: Option<u32>,
+    state: State<'_, AppState>,
+) -> Result<Vec<NearDuplicatePages>> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    let max_distance = max_distance.unwrap_or(DEFAULT_NEAR_DUPLICATE_DISTANCE);
+    if max_distance > MAX_NEAR_DUPLICATE_DISTANCE {
+        return Err(AppError::Custom(format!(
+            "Maximum distance must be at most {} bits",
+            MAX_NEAR_DUPLICATE_DISTANCE
+        )));
+    }
+    let limit = limit.unwrap_or(DEFAULT_NEAR_DUPLICATE_LIMIT);
+
+    let Some(document_id) = document_id else {
+        let (pages, hashes): (Vec<FingerprintedPage>, Vec<u64>) = fingerprinted_pages(&pool, None)
+            .await?
+            .into_iter()
+            .filter_map(|page| parse_dhash(&page.dhash).map(|hash| (page, hash)))
+            .unzip();
+
+        let pairs =
+            spawn_blocking(move || near_duplicate_pairs(&hashes, max_distance, limit as usize))
+                .await
+                .map_err(|e| AppError::Custom(format!("Task join error: {}", e)))?;
+
+        return Ok(pairs
+            .into_iter()
+            .map(|(i, j, distance)| NearDuplicatePages {
+                first: pages[i].clone(),
+                second: pages[j].clone(),
+                distance,
+            })
+            .collect());
+    };
+
+    // Pairs within the document are reported once, pairs with other
+    // documents with the document's page first
+    let pairs: Vec<(i64, i64, i32)> = sqlx::query_as(
+        r#"
+        WITH fingerprinted AS (
+            SELECT id, document_id,
+                   ('x' || (page_metadata->'fingerprint'->>'dhash'))::bit(64) AS dhash
+            FROM page
+            WHERE page_metadata->'fingerprint'->>'dhash' ~ '^[0-9a-f]{16}$'
+              AND NOT COALESCE((page_metadata->'fingerprint'->>'blank')::boolean, FALSE)
+        )
+        SELECT t.id, c.id, bit_count(t.dhash # c.dhash)::int AS distance
+        FROM fingerprinted t
+        JOIN fingerprinted c ON c.id <> t.id
+            AND (c.document_id <> t.document_id OR c.id > t.id)
+        WHERE t.document_id = $1
+          AND bit_count(t.dhash # c.dhash) <= $2
+        ORDER BY distance, t.id, c.id
+        LIMIT $3
+        "#,
+    )
+    .bind(document_id)
+    .bind(max_distance as i32)
+    .bind(limit as i64)
+    .fetch_all(&pool)
+    .await?;
+
+    let page_ids: Vec<i64> = pairs
+        .iter()
+        .flat_map(|&(first, second, _)| [first, second])
+        .collect();
+    let pages: HashMap<i64, FingerprintedPage> = fingerprinted_pages(&pool, Some(&page_ids))
+        .await?
+        .into_iter()
+        .map(|page| (page.page_id, page))
+        .collect();
+
+    Ok(pairs
+        .into_iter()
+        .filter_map(|(first, second, distance)| {
+            Some(NearDuplicatePages {
+                first: pages.get(&first)?.clone(),
+                second: pages.get(&second)?.clone(),
+                distance: distance as u32,
+            })
+        })
+        .collect())
+}
+
+/// Non-blank pages with a perceptual hash, all of them or those in `page_ids`
+async fn fingerprinted_pages(
+    pool: &PgPool,
+    page_ids: Option<&[i64]>,
+) -> Result<Vec<FingerprintedPage>> {
+    let pages = sqlx::query_as::<_, FingerprintedPage>(
+        r#"
+        SELECT p.id AS page_id, p.document_id, d.title AS document_title, p.page_num,
+               p.page_metadata->'fingerprint'->>'dhash' AS dhash
+        FROM page p
+        JOIN document d ON p.document_id = d.id
+        WHERE p.page_metadata->'fingerprint'->>'dhash' IS NOT NULL
+          AND NOT COALESCE((p.page_metadata->'fingerprint'->>'blank')::boolean, FALSE)
+          AND ($1::bigint[] IS NULL OR p.id = ANY($1))
+        ORDER BY p.document_id, p.page_num
+        "#,
+    )
+    .bind(page_ids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(pages)
+}
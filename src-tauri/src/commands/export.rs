@@ -9,6 +9,7 @@ use zip::write::FileOptions;
 use zip::ZipWriter;
 
 use crate::error::{AppError, Result};
+use crate::ingest::extension_for_mimetype;
 use crate::state::AppState;
 
 /// Configuration for export operation
@@ -413,7 +414,14 @@ struct ImageChunkRow {
     parent_page: Option<i64>,
     mimetype: String,
     page_num: Option<i32>,
+    page_label: Option<String>,
     document_id: Option<i64>,
+    width: Option<i32>,
+    height: Option<i32>,
+    dpi: Option<i32>,
+    byte_size: Option<i64>,
+    sha256: Option<String>,
+    renderer: Option<String>,
 }
 
 async fn export_image_chunks_csv(
@@ -422,7 +430,14 @@ async fn export_image_chunks_csv(
     app_handle: &AppHandle,
 ) -> Result<u32> {
     let rows = sqlx::query_as::<_, ImageChunkRow>(
-        "SELECT ic.id, ic.parent_page, ic.mimetype, p.page_num, p.document_id
+        "SELECT ic.id, ic.parent_page, ic.mimetype, p.page_num,
+                p.page_metadata->>'page_label' AS page_label, p.document_id,
+                (ic.chunk_metadata->'image'->>'width')::int AS width,
+                (ic.chunk_metadata->'image'->>'height')::int AS height,
+                (ic.chunk_metadata->'image'->>'dpi')::int AS dpi,
+                (ic.chunk_metadata->'image'->>'byte_size')::bigint AS byte_size,
+                ic.chunk_metadata->'image'->>'sha256' AS sha256,
+                ic.chunk_metadata->'image'->>'renderer' AS renderer
          FROM image_chunk ic
          LEFT JOIN page p ON ic.parent_page = p.id
          ORDER BY ic.id",
@@ -438,7 +453,20 @@ async fn export_image_chunks_csv(
     let mut wtr = csv::Writer::from_writer(file);
 
     // Write header
-    wtr.write_record(["id", "parent_page", "mimetype", "page_num", "document_id"])?;
+    wtr.write_record([
+        "id",
+        "parent_page",
+        "mimetype",
+        "page_num",
+        "page_label",
+        "document_id",
+        "width",
+        "height",
+        "dpi",
+        "byte_size",
+        "sha256",
+        "renderer",
+    ])?;
 
     for (i, row) in rows.iter().enumerate() {
         wtr.write_record([
@@ -448,9 +476,16 @@ async fn export_image_chunks_csv(
                 .unwrap_or_default(),
             row.mimetype.clone(),
             row.page_num.map(|n| n.to_string()).unwrap_or_default(),
+            row.page_label.clone().unwrap_or_default(),
             row.document_id
                 .map(|id| id.to_string())
                 .unwrap_or_default(),
+            row.width.map(|n| n.to_string()).unwrap_or_default(),
+            row.height.map(|n| n.to_string()).unwrap_or_default(),
+            row.dpi.map(|n| n.to_string()).unwrap_or_default(),
+            row.byte_size.map(|n| n.to_string()).unwrap_or_default(),
+            row.sha256.clone().unwrap_or_default(),
+            row.renderer.clone().unwrap_or_default(),
         ])?;
 
         if (i + 1) % 100 == 0 || i + 1 == rows.len() {
@@ -470,6 +505,7 @@ async fn export_image_chunks_csv(
 struct ImageContentRow {
     id: i64,
     contents: Vec<u8>,
+    mimetype: String,
 }
 
 async fn export_images(
@@ -496,7 +532,7 @@ async fn export_images(
 
     loop {
         let rows = sqlx::query_as::<_, ImageContentRow>(
-            "SELECT id, contents FROM image_chunk ORDER BY id LIMIT $1 OFFSET $2",
+            "SELECT id, contents, mimetype FROM image_chunk ORDER BY id LIMIT $1 OFFSET $2",
         )
         .bind(batch_size)
         .bind(offset)
@@ -508,7 +544,8 @@ async fn export_images(
         }
 
         for row in &rows {
-            let image_path = images_dir.join(format!("{}.png", row.id));
+            let extension = extension_for_mimetype(&row.mimetype);
+            let image_path = images_dir.join(format!("{}.{}", row.id, extension));
             let mut file = File::create(&image_path)?;
             file.write_all(&row.contents)?;
             exported += 1;
@@ -1,21 +1,31 @@
+use std::collections::HashMap;
+
 use serde::Serialize;
-use tauri::State;
+use sqlx::types::Json;
+use sqlx::PgConnection;
+use tauri::{AppHandle, Manager, State};
+use tokio::task::spawn_blocking;
 
+use crate::db::{resolve_source, ImageChunkInfo, ImageInfo};
 use crate::error::{AppError, Result};
+use crate::ingest::{crop_region, decode_image, make_thumbnails, BoundingBox, Thumbnail};
 use crate::state::AppState;
 
 /// Get the source file path for a document (from the `file` table).
+/// When the file is not on this machine, a local copy of it from the
+/// content store is returned instead, if it was stored.
 /// Returns None if the document has no linked file record.
 #[tauri::command]
 pub async fn get_source_file_url(
     document_id: i64,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<Option<String>> {
     let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
 
-    let row: Option<(String,)> = sqlx::query_as(
+    let row: Option<(String, Option<String>)> = sqlx::query_as(
         r#"
-        SELECT f.path
+        SELECT f.path, d.doc_metadata->>'source_sha256'
         FROM file f
         JOIN document d ON d.path = f.id
         WHERE d.id = $1
@@ -25,7 +35,18 @@ pub async fn get_source_file_url(
     .fetch_optional(&pool)
     .await?;
 
-    Ok(row.map(|(path,)| path))
+    match row {
+        Some((path, sha256)) => {
+            let cache_dir = app_handle
+                .path()
+                .app_cache_dir()
+                .map_err(|e| AppError::Custom(format!("No cache directory: {}", e)))?;
+            Ok(Some(
+                resolve_source(&pool, &cache_dir, &path, sha256.as_deref()).await?,
+            ))
+        }
+        None => Ok(None),
+    }
 }
 
 #[derive(Serialize)]
@@ -39,17 +60,20 @@ pub struct PageSourceInfo {
 /// Get per-page source paths for all pages in a document.
 /// For image documents, source_path comes from page_metadata.
 /// For PDF documents, source_path comes from the file table.
+/// Sources missing on this machine resolve as in `get_source_file_url`.
 #[tauri::command]
 pub async fn get_page_source_urls(
     document_id: i64,
+    app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<Vec<PageSourceInfo>> {
     let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
 
-    let rows: Vec<(i64, i32, Option<String>)> = sqlx::query_as(
+    let rows: Vec<(i64, i32, Option<String>, Option<String>)> = sqlx::query_as(
         r#"
         SELECT p.id, p.page_num,
-               COALESCE(p.page_metadata->>'source_path', f.path) as source_path
+               COALESCE(p.page_metadata->>'source_path', f.path) as source_path,
+               COALESCE(p.page_metadata->>'source_sha256', d.doc_metadata->>'source_sha256')
         FROM page p
         JOIN document d ON p.document_id = d.id
         LEFT JOIN file f ON d.path = f.id
@@ -61,7 +85,7 @@ pub async fn get_page_source_urls(
     .fetch_all(&pool)
     .await?;
 
-    let page_ids: Vec<i64> = rows.iter().map(|(id, _, _)| *id).collect();
+    let page_ids: Vec<i64> = rows.iter().map(|(id, _, _, _)| *id).collect();
 
     // Batch-fetch chunk IDs for all pages
     let chunk_rows: Vec<(i64, i64)> = sqlx::query_as(
@@ -71,15 +95,34 @@ pub async fn get_page_source_urls(
     .fetch_all(&pool)
     .await?;
 
-    let mut result: Vec<PageSourceInfo> = rows
-        .into_iter()
-        .map(|(page_id, page_num, source_path)| PageSourceInfo {
+    let cache_dir = app_handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| AppError::Custom(format!("No cache directory: {}", e)))?;
+
+    // Pages of a PDF or multi-page file share their source
+    let mut resolved: HashMap<String, String> = HashMap::new();
+    let mut result: Vec<PageSourceInfo> = Vec::with_capacity(rows.len());
+    for (page_id, page_num, source_path, sha256) in rows {
+        let source_path = match source_path {
+            Some(path) => match resolved.get(&path) {
+                Some(local_path) => Some(local_path.clone()),
+                None => {
+                    let local_path =
+                        resolve_source(&pool, &cache_dir, &path, sha256.as_deref()).await?;
+                    resolved.insert(path, local_path.clone());
+                    Some(local_path)
+                }
+            },
+            None => None,
+        };
+        result.push(PageSourceInfo {
             page_id,
             chunk_ids: Vec::new(),
             page_num,
             source_path,
-        })
-        .collect();
+        });
+    }
 
     // Assign chunk IDs to their pages
     for (parent_page, chunk_id) in chunk_rows {
@@ -113,3 +156,235 @@ pub async fn get_chunk_data_url(
     let base64_data = STANDARD.encode(&contents);
     Ok(format!("data:{};base64,{}", mimetype, base64_data))
 }
+
+/// Get a chunk's thumbnail as a data: URL, using the smallest stored size
+/// of at least `size` pixels, or the largest one below it. Chunks without
+/// thumbnails (older imports, region chunks) get one generated and stored
+/// on first request.
+#[tauri::command]
+pub async fn get_chunk_thumbnail_data_url(
+    chunk_id: i64,
+    size: u32,
+    state: State<'_, AppState>,
+) -> Result<String> {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    if size == 0 {
+        return Err(AppError::ImageError(
+            "Thumbnail size must be greater than 0".to_string(),
+        ));
+    }
+
+    let stored: Option<(Vec<u8>, String)> = sqlx::query_as(
+        r#"
+        SELECT contents, mimetype
+        FROM image_chunk_thumbnail
+        WHERE image_chunk_id = $1
+        ORDER BY size < $2, CASE WHEN size >= $2 THEN size ELSE -size END
+        LIMIT 1
+        "#,
+    )
+    .bind(chunk_id)
+    .bind(size as i32)
+    .fetch_optional(&pool)
+    .await?;
+
+    let (contents, mimetype) = match stored {
+        Some(row) => row,
+        None => {
+            let contents: Vec<u8> =
+                sqlx::query_scalar("SELECT contents FROM image_chunk WHERE id = $1")
+                    .bind(chunk_id)
+                    .fetch_optional(&pool)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::NotFound(format!("Image chunk {} not found", chunk_id))
+                    })?;
+
+            let thumbnails = spawn_blocking(move || {
+                let img = decode_image(&contents)?;
+                make_thumbnails(&img, &[size])
+            })
+            .await
+            .map_err(|e| AppError::ImageError(format!("Task join error: {}", e)))??;
+
+            let mut conn = pool.acquire().await?;
+            insert_thumbnails(&mut conn, chunk_id, &thumbnails).await?;
+
+            let thumbnail = thumbnails
+                .into_iter()
+                .next()
+                .ok_or_else(|| AppError::ImageError("Failed to create thumbnail".to_string()))?;
+            (thumbnail.bytes, thumbnail.mimetype.to_string())
+        }
+    };
+
+    let base64_data = STANDARD.encode(&contents);
+    Ok(format!("data:{};base64,{}", mimetype, base64_data))
+}
+
+/// Store thumbnails of an image chunk, keeping any already stored at the same size
+pub(crate) async fn insert_thumbnails(
+    conn: &mut PgConnection,
+    chunk_id: i64,
+    thumbnails: &[Thumbnail],
+) -> Result<()> {
+    for thumbnail in thumbnails {
+        sqlx::query(
+            r#"INSERT INTO image_chunk_thumbnail (image_chunk_id, size, contents, mimetype)
+               VALUES ($1, $2, $3, $4)
+               ON CONFLICT (image_chunk_id, size) DO NOTHING"#,
+        )
+        .bind(chunk_id)
+        .bind(thumbnail.size as i32)
+        .bind(&thumbnail.bytes)
+        .bind(thumbnail.mimetype)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Crop a region out of a page's full-page image and store it as a new
+/// image chunk, so figures and tables can be used as evidence on their own.
+/// The bbox is given in fractions of the page size and kept in `chunk_metadata`.
+#[tauri::command]
+pub async fn create_image_chunk_from_region(
+    page_id: i64,
+    bbox: BoundingBox,
+    state: State<'_, AppState>,
+) -> Result<ImageChunkInfo> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    bbox.validate()?;
+
+    // Chunks ingested before chunk_metadata existed are full-page chunks
+    let source: (i64, Vec<u8>, String, Option<Json<ImageInfo>>) = sqlx::query_as(
+        r#"
+        SELECT id, contents, mimetype, chunk_metadata->'image'
+        FROM image_chunk
+        WHERE parent_page = $1
+          AND (chunk_metadata IS NULL OR chunk_metadata->>'kind' = 'page')
+        ORDER BY id
+        LIMIT 1
+        "#,
+    )
+    .bind(page_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No page image found for page {}", page_id)))?;
+
+    let (source_chunk_id, contents, mimetype, source_image) = source;
+    let region = spawn_blocking(move || crop_region(&contents, &mimetype, &bbox))
+        .await
+        .map_err(|e| AppError::ImageError(format!("Task join error: {}", e)))??;
+
+    let chunk_metadata = serde_json::json!({
+        "kind": "region",
+        "source": "manual",
+        "bbox": bbox,
+        "pixel_bbox": region.rect,
+        "source_chunk_id": source_chunk_id,
+        "image": region.image_info(source_image.as_ref().map(|image| &image.0)),
+    });
+
+    let chunk = sqlx::query_as::<_, ImageChunkInfo>(
+        r#"
+        INSERT INTO image_chunk (parent_page, contents, mimetype, chunk_metadata)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, parent_page, mimetype, chunk_metadata, chunk_metadata->'image' AS image
+        "#,
+    )
+    .bind(page_id)
+    .bind(&region.bytes)
+    .bind(region.mimetype)
+    .bind(&chunk_metadata)
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(chunk)
+}
+
+/// Accept an automatically detected region chunk after review.
+/// Rejected candidates are removed with `delete_image_chunk`.
+#[tauri::command]
+pub async fn accept_auto_region(
+    chunk_id: i64,
+    state: State<'_, AppState>,
+) -> Result<ImageChunkInfo> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    sqlx::query_as::<_, ImageChunkInfo>(
+        r#"
+        UPDATE image_chunk
+        SET chunk_metadata = chunk_metadata || '{"status": "accepted"}'::jsonb
+        WHERE id = $1 AND chunk_metadata->>'source' = 'auto'
+        RETURNING id, parent_page, mimetype, chunk_metadata, chunk_metadata->'image' AS image
+        "#,
+    )
+    .bind(chunk_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No detected region with id {}", chunk_id)))
+}
+
+/// Delete a region chunk (manual or detected).
+/// Full-page chunks belong to their page and are removed with the document;
+/// chunks used as evidence in retrieval_relation cannot be deleted.
+#[tauri::command]
+pub async fn delete_image_chunk(chunk_id: i64, state: State<'_, AppState>) -> Result<bool> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    let kind: Option<String> = sqlx::query_scalar(
+        "SELECT chunk_metadata->>'kind' FROM image_chunk WHERE id = $1",
+    )
+    .bind(chunk_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Image chunk {} not found", chunk_id)))?;
+
+    if kind.as_deref() != Some("region") {
+        return Err(AppError::Custom(format!(
+            "Image chunk {} is a full-page chunk and cannot be deleted on its own",
+            chunk_id
+        )));
+    }
+
+    let relation_count: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM retrieval_relation WHERE image_chunk_id = $1",
+    )
+    .bind(chunk_id)
+    .fetch_one(&pool)
+    .await?;
+
+    if relation_count > 0 {
+        return Err(AppError::Custom(format!(
+            "Image chunk {} is referenced by {} retrieval relation(s)",
+            chunk_id, relation_count
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM image_chunk_retrieved_result WHERE image_chunk_id = $1")
+        .bind(chunk_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query("DELETE FROM image_chunk_thumbnail WHERE image_chunk_id = $1")
+        .bind(chunk_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let result = sqlx::query("DELETE FROM image_chunk WHERE id = $1")
+        .bind(chunk_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -1,24 +1,115 @@
-use std::io::Cursor;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use image::io::Reader as ImageReader;
-use tauri::{AppHandle, Emitter, State};
+use image::DynamicImage;
+use sqlx::types::Json;
+use sqlx::{PgConnection, PgPool};
+use tauri::{AppHandle, Manager, State};
 use tokio::task::spawn_blocking;
 
+use crate::db::{materialize_source, store_source, Chunk, ImageInfo, PageInfo};
 use crate::error::{AppError, Result};
-use crate::ingest::{process_pdf, IngestionProgress, IngestionResult};
-use crate::state::AppState;
+use crate::ingest::{
+    browser_mimetype, combined_sha256, convert_to_pdf, crop_image, decode_image, encode_image,
+    file_sha256, fingerprint_page, image_extensions, image_info, load_image_frames,
+    make_thumbnails, office_extension, parse_page_ranges, renderer, scan_directory, stream_pages,
+    BlankPagePolicy, BoundingBox, ChunkingStrategy, ConvertedPdf, CroppedRegion,
+    DirectoryIngestionReport, DirectoryScanOptions, DuplicatePolicy, IngestOptions, IngestTarget,
+    IngestionProgress, IngestionResult, PageFingerprint, RegionCandidate, RenderOptions,
+    RerenderResult, SourceImageInfo, SupportedFormats, Thumbnail, IMAGE_RENDERER,
+    OFFICE_EXTENSIONS,
+};
+use crate::state::{AppState, IngestionJob, IngestionJobInfo};
 
-/// Ingest a PDF file into the database
+use super::documents::{ensure_image_document, refresh_image_document_hash};
+use super::images::insert_thumbnails;
+
+/// Ingest a PDF file into the database.
+/// Pages are rendered in parallel and inserted in page order as they arrive,
+/// so memory use does not grow with the page count. The file's SHA-256 is
+/// checked against earlier imports according to `options.on_duplicate`.
+/// Runs as a job that `cancel_ingestion` can stop between pages, in which
+/// case nothing is committed. Office documents (see `OFFICE_EXTENSIONS`) are
+/// converted to PDF with LibreOffice first and recorded under their own path.
+/// Encrypted PDFs are opened with `password`, which is not stored; without
+/// a correct one the import fails with `PdfPasswordRequired` or
+/// `PdfWrongPassword`. Unless built with the `pdfium` feature, the password
+/// is passed to poppler on the command line, where other users of the
+/// machine can read it from the process list. `options.pages` limits the import to some pages,
+/// which keep their original page numbers; more can be added later with
+/// `import_pdf_pages`.
 #[tauri::command]
 pub async fn ingest_pdf(
     file_path: String,
     title: Option<String>,
     author: Option<String>,
+    password: Option<String>,
+    options: Option<IngestOptions>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<IngestionResult> {
     let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+    let job = state.jobs.start("pdf", &file_path);
+
+    let source = PdfSource {
+        file_path,
+        title,
+        author,
+        password,
+    };
+    let result = import_pdf(
+        &pool,
+        &app_handle,
+        &job,
+        source,
+        options.unwrap_or_default(),
+    )
+    .await?;
+
+    job.report(&app_handle, IngestionProgress::complete(result.page_count));
+    Ok(result)
+}
+
+/// A PDF or office document to import, with what the caller supplied for it
+struct PdfSource {
+    file_path: String,
+    /// Overrides the title from the PDF metadata
+    title: Option<String>,
+    /// Overrides the author from the PDF metadata
+    author: Option<String>,
+    /// Opens encrypted PDFs
+    password: Option<String>,
+}
+
+impl PdfSource {
+    fn new(file_path: String) -> Self {
+        Self {
+            file_path,
+            title: None,
+            author: None,
+            password: None,
+        }
+    }
+}
+
+/// Ingest one PDF or office document as part of `job`,
+/// shared by `ingest_pdf` and `ingest_directory`. Reporting completion is
+/// left to the caller, which may import more items under the same job.
+async fn import_pdf(
+    pool: &PgPool,
+    app_handle: &AppHandle,
+    job: &IngestionJob,
+    source: PdfSource,
+    options: IngestOptions,
+) -> Result<IngestionResult> {
+    let PdfSource {
+        file_path,
+        title,
+        author,
+        password,
+    } = source;
+
+    validate_pdf_options(&options)?;
 
     let path = PathBuf::from(&file_path);
 
@@ -27,18 +118,46 @@ pub async fn ingest_pdf(
         .and_then(|n| n.to_str())
         .map(|s| s.to_string());
 
-    let _ = app_handle.emit("ingestion-progress", IngestionProgress::reading(0));
+    job.report(app_handle, IngestionProgress::reading(0));
 
-    let pdf_result = spawn_blocking(move || process_pdf(&path))
+    let hash_path = path.clone();
+    let source_sha256 = spawn_blocking(move || file_sha256(&hash_path))
         .await
         .map_err(|e| AppError::PdfError(format!("Task join error: {}", e)))??;
 
-    let page_count = pdf_result.page_count;
+    let duplicate_of = find_duplicate(pool, &source_sha256).await?;
+    if let Some(existing_id) = duplicate_of {
+        match options.on_duplicate {
+            DuplicatePolicy::Refuse => return Err(AppError::DuplicateDocument(existing_id)),
+            DuplicatePolicy::Link => return linked_result(pool, existing_id).await,
+            DuplicatePolicy::Force => {}
+        }
+    }
+
+    let office_format = office_extension(&path);
+    let converted = convert_office_document(app_handle, job, &path).await?;
+    let pdf_path = converted
+        .as_ref()
+        .map_or_else(|| path.clone(), |c| c.path().to_path_buf());
+
+    let info_path = pdf_path.clone();
+    let info_password = password.clone();
+    let pdf_info =
+        spawn_blocking(move || renderer().read_info(&info_path, info_password.as_deref()))
+            .await
+            .map_err(|e| AppError::PdfError(format!("Task join error: {}", e)))??;
 
-    let final_title = title.or(pdf_result.metadata.title);
-    let final_author = author.or(pdf_result.metadata.author);
+    let page_count = pdf_info.page_count;
+    let page_nums = match &options.pages {
+        Some(pages) => parse_page_ranges(pages, page_count)?,
+        None => (1..=page_count).collect(),
+    };
 
-    let _ = app_handle.emit("ingestion-progress", IngestionProgress::reading(page_count));
+    let final_title = title.or_else(|| pdf_info.metadata.title.clone());
+    let final_author = author.or_else(|| pdf_info.metadata.author.clone());
+
+    job.ensure_active(app_handle)?;
+    job.report(app_handle, IngestionProgress::reading(page_count));
 
     let mut tx = pool.begin().await?;
 
@@ -48,78 +167,942 @@ pub async fn ingest_pdf(
             .fetch_one(&mut *tx)
             .await?;
 
+    let mut doc_metadata = document_metadata(&source_sha256, duplicate_of, &options);
+    if let (Some(format), Some(converted)) = (&office_format, &converted) {
+        doc_metadata["converted_from"] = serde_json::json!({
+            "format": format,
+            "converter": converted.converter,
+        });
+    }
+    doc_metadata["pdf_info"] = serde_json::json!(pdf_info.metadata);
+    if !pdf_info.outline.is_empty() {
+        doc_metadata["outline"] = serde_json::json!(pdf_info.outline);
+    }
+    doc_metadata["source_page_count"] = page_count.into();
+
     let document_id: i64 = sqlx::query_scalar(
-        r#"INSERT INTO document (path, filename, author, title) VALUES ($1, $2, $3, $4) RETURNING id"#,
+        r#"INSERT INTO document (path, filename, author, title, doc_metadata)
+           VALUES ($1, $2, $3, $4, $5) RETURNING id"#,
     )
     .bind(file_id)
     .bind(&filename)
     .bind(&final_author)
     .bind(&final_title)
+    .bind(&doc_metadata)
     .fetch_one(&mut *tx)
     .await?;
 
-    let mimetype = "image/png".to_string();
+    if options.store_sources {
+        store_source(&mut tx, &path, &source_sha256).await?;
+    }
+
+    let pages = PdfPages {
+        document_id,
+        file_path,
+        pdf_path,
+        password,
+        page_nums,
+        page_labels: pdf_info.page_labels,
+    };
+    let stored = store_pdf_pages(&mut tx, app_handle, job, pages, options).await?;
+
+    job.ensure_active(app_handle)?;
+    tx.commit().await?;
+
+    Ok(IngestionResult {
+        file_id,
+        document_id,
+        page_count: stored.page_count,
+        image_chunk_count: stored.image_chunk_count,
+        chunk_count: stored.chunk_count,
+        duplicate_of,
+        blank_pages: stored.blank_pages,
+    })
+}
+
+/// Import more pages of a document's source PDF, e.g. chapters left out by
+/// the `pages` option of `ingest_pdf`. `pages` takes the same ranges, and
+/// pages the document already has are skipped, so the result only counts
+/// what was added.
+///
+/// The source file must be unchanged since the document was imported.
+/// Without `options`, the render settings of the document's pages and its
+/// recorded chunking strategy are reused so the new pages match.
+#[tauri::command]
+pub async fn import_pdf_pages(
+    document_id: i64,
+    pages: String,
+    password: Option<String>,
+    options: Option<IngestOptions>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<IngestionResult> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    let (file_id, file_path, source_sha256) = pdf_document_source(&pool, document_id).await?;
+
+    let options = match options {
+        Some(options) => options,
+        None => document_ingest_options(&pool, document_id).await?,
+    };
+    validate_pdf_options(&options)?;
+
+    let job = state.jobs.start("pdf", &file_path);
+    job.report(&app_handle, IngestionProgress::reading(0));
+
+    let path = source_as_imported(&app_handle, &pool, Path::new(&file_path), source_sha256).await?;
+
+    let converted = convert_office_document(&app_handle, &job, &path).await?;
+    let pdf_path = converted
+        .as_ref()
+        .map_or_else(|| path.clone(), |c| c.path().to_path_buf());
+
+    let info_path = pdf_path.clone();
+    let info_password = password.clone();
+    let pdf_info =
+        spawn_blocking(move || renderer().read_info(&info_path, info_password.as_deref()))
+            .await
+            .map_err(|e| AppError::PdfError(format!("Task join error: {}", e)))??;
+
+    let existing: Vec<i32> =
+        sqlx::query_scalar(r#"SELECT page_num FROM page WHERE document_id = $1"#)
+            .bind(document_id)
+            .fetch_all(&pool)
+            .await?;
+    let mut page_nums = parse_page_ranges(&pages, pdf_info.page_count)?;
+    page_nums.retain(|page_num| !existing.contains(page_num));
+
+    job.ensure_active(&app_handle)?;
+    job.report(&app_handle, IngestionProgress::reading(pdf_info.page_count));
+
+    let mut tx = pool.begin().await?;
+
+    let pages = PdfPages {
+        document_id,
+        file_path,
+        pdf_path,
+        password,
+        page_nums,
+        page_labels: pdf_info.page_labels,
+    };
+    let stored = store_pdf_pages(&mut tx, &app_handle, &job, pages, options).await?;
+
+    job.ensure_active(&app_handle)?;
+    tx.commit().await?;
+
+    job.report(&app_handle, IngestionProgress::complete(stored.page_count));
+
+    Ok(IngestionResult {
+        file_id,
+        document_id,
+        page_count: stored.page_count,
+        image_chunk_count: stored.image_chunk_count,
+        chunk_count: stored.chunk_count,
+        duplicate_of: None,
+        blank_pages: stored.blank_pages,
+    })
+}
+
+/// Re-render the pages of a PDF document from its source file with new
+/// render options, e.g. at a higher resolution. Chunk ids stay the same so
+/// `retrieval_relation` evidence remains valid: page images are replaced in
+/// place and region chunks are cropped again from their `bbox`.
+///
+/// Page thumbnails are regenerated and region thumbnails are recreated on
+/// request; text chunks and fingerprints are kept. The source file must be
+/// unchanged since the document was imported. Runs as a job that
+/// `cancel_ingestion` can stop, in which case nothing is changed.
+#[tauri::command]
+pub async fn rerender_document(
+    document_id: i64,
+    password: Option<String>,
+    options: RenderOptions,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<RerenderResult> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+
+    let (_, file_path, source_sha256) = pdf_document_source(&pool, document_id).await?;
+    let options = IngestOptions {
+        render: options,
+        fingerprint: false,
+        ..IngestOptions::default()
+    };
+    validate_pdf_options(&options)?;
+
+    let pages: Vec<(i64, i32, Option<serde_json::Value>)> = sqlx::query_as(
+        r#"SELECT id, page_num, page_metadata FROM page WHERE document_id = $1 ORDER BY page_num"#,
+    )
+    .bind(document_id)
+    .fetch_all(&pool)
+    .await?;
+    let page_ids: Vec<i64> = pages.iter().map(|(id, _, _)| *id).collect();
+    let mut regions = page_regions(&pool, &page_ids).await?;
+
+    let job = state.jobs.start("pdf", &file_path);
+    job.report(&app_handle, IngestionProgress::reading(0));
+
+    let path = source_as_imported(&app_handle, &pool, Path::new(&file_path), source_sha256).await?;
+
+    let converted = convert_office_document(&app_handle, &job, &path).await?;
+    let pdf_path = converted
+        .as_ref()
+        .map_or_else(|| path.clone(), |c| c.path().to_path_buf());
+
+    let info_path = pdf_path.clone();
+    let info_password = password.clone();
+    let pdf_info =
+        spawn_blocking(move || renderer().read_info(&info_path, info_password.as_deref()))
+            .await
+            .map_err(|e| AppError::PdfError(format!("Task join error: {}", e)))??;
+
+    if let Some(&(_, page_num, _)) = pages.last() {
+        if page_num > pdf_info.page_count {
+            return Err(AppError::Custom(format!(
+                "Document {} has page {} but {} only has {} pages",
+                document_id, page_num, file_path, pdf_info.page_count
+            )));
+        }
+    }
+
+    job.ensure_active(&app_handle)?;
+    job.report(&app_handle, IngestionProgress::reading(pdf_info.page_count));
+
+    let mimetype = options.render.format.mimetype();
+    let render_metadata = serde_json::to_value(&options.render)
+        .map_err(|e| AppError::Custom(format!("Failed to serialize render options: {}", e)))?;
+    let total_pages = pages.len() as i32;
+    let mut result = RerenderResult {
+        document_id,
+        page_count: 0,
+        image_chunk_count: 0,
+    };
+
+    let mut tx = pool.begin().await?;
+
+    // Dropping the receiver on error or cancellation stops the renderer;
+    // dropping the transaction keeps the previous images.
+    let page_nums = pages.iter().map(|(_, page_num, _)| *page_num).collect();
+    let mut pages: HashMap<i32, (i64, Option<serde_json::Value>)> = pages
+        .into_iter()
+        .map(|(id, page_num, page_metadata)| (page_num, (id, page_metadata)))
+        .collect();
+    let mut rendered_pages = stream_pages(pdf_path, password, page_nums, options);
+    while let Some(rendered) = rendered_pages.recv().await {
+        job.ensure_active(&app_handle)?;
+        let rendered = rendered?;
+        result.page_count += 1;
+        job.report(
+            &app_handle,
+            IngestionProgress::rendering(result.page_count, total_pages),
+        );
+
+        let (page_id, page_metadata) = pages
+            .remove(&rendered.page_num)
+            .ok_or_else(|| AppError::PdfError(format!("Unexpected page {}", rendered.page_num)))?;
+        let mut page_metadata = page_metadata.unwrap_or_default();
+        page_metadata["render"] = render_metadata.clone();
 
-    for (page_idx, png_bytes) in pdf_result.pages.into_iter().enumerate() {
-        let page_metadata = serde_json::json!({"source_path": file_path});
-        let page_id: i64 = sqlx::query_scalar(
-            r#"INSERT INTO page (page_num, document_id, image_contents, mimetype, page_metadata)
-               VALUES ($1, $2, NULL, $3, $4) RETURNING id"#,
+        // Regions are cropped again at the same fractions of the new page image
+        let boxes = regions.remove(&page_id).unwrap_or_default();
+        let (bytes, crops) = spawn_blocking(move || {
+            let crops = crop_regions(&decode_image(&rendered.bytes)?, mimetype, boxes)?;
+            Ok::<_, AppError>((rendered.bytes, crops))
+        })
+        .await
+        .map_err(|e| AppError::ImageError(format!("Task join error: {}", e)))??;
+
+        let page_chunk_id = replace_page_contents(
+            &mut tx,
+            page_id,
+            &bytes,
+            mimetype,
+            page_metadata,
+            &rendered.image,
         )
-        .bind((page_idx + 1) as i32)
-        .bind(document_id)
-        .bind(&mimetype)
+        .await?;
+        insert_thumbnails(&mut tx, page_chunk_id, &rendered.thumbnails).await?;
+        result.image_chunk_count += 1;
+        result.image_chunk_count += update_region_crops(&mut tx, crops, &rendered.image).await?;
+    }
+
+    // Keep the previous images when the renderer stopped early
+    if !pages.is_empty() {
+        return Err(AppError::PdfError(format!(
+            "Rendering stopped after {} of {} pages",
+            result.page_count, total_pages
+        )));
+    }
+
+    job.ensure_active(&app_handle)?;
+    tx.commit().await?;
+
+    job.report(&app_handle, IngestionProgress::complete(result.page_count));
+
+    Ok(result)
+}
+
+/// Replace the full-page image and `page_metadata` of a page, dropping
+/// the stale thumbnails of all its image chunks. The page chunk keeps its
+/// id. Returns the page chunk id.
+async fn replace_page_contents(
+    conn: &mut PgConnection,
+    page_id: i64,
+    contents: &[u8],
+    mimetype: &str,
+    mut page_metadata: serde_json::Value,
+    image: &ImageInfo,
+) -> Result<i64> {
+    page_metadata["image"] = serde_json::json!(image);
+
+    sqlx::query(r#"UPDATE page SET mimetype = $2, page_metadata = $3 WHERE id = $1"#)
+        .bind(page_id)
+        .bind(mimetype)
         .bind(&page_metadata)
-        .fetch_one(&mut *tx)
+        .execute(&mut *conn)
         .await?;
 
-        sqlx::query_scalar::<_, i64>(
-            r#"INSERT INTO image_chunk (parent_page, contents, mimetype)
-               VALUES ($1, $2, $3) RETURNING id"#,
+    // Chunks ingested before chunk_metadata existed are full-page chunks
+    let chunk_id: i64 = sqlx::query_scalar(
+        r#"
+        UPDATE image_chunk
+        SET contents = $2, mimetype = $3,
+            chunk_metadata = COALESCE(chunk_metadata, '{"kind": "page"}'::jsonb)
+                || jsonb_build_object('image', $4::jsonb)
+        WHERE id = (
+            SELECT id FROM image_chunk
+            WHERE parent_page = $1
+              AND (chunk_metadata IS NULL OR chunk_metadata->>'kind' = 'page')
+            ORDER BY id
+            LIMIT 1
         )
-        .bind(page_id)
-        .bind(&png_bytes)
-        .bind(&mimetype)
-        .fetch_one(&mut *tx)
+        RETURNING id
+        "#,
+    )
+    .bind(page_id)
+    .bind(contents)
+    .bind(mimetype)
+    .bind(serde_json::json!(image))
+    .fetch_optional(&mut *conn)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("No page image found for page {}", page_id)))?;
+
+    sqlx::query(
+        r#"DELETE FROM image_chunk_thumbnail
+           WHERE image_chunk_id IN (SELECT id FROM image_chunk WHERE parent_page = $1)"#,
+    )
+    .bind(page_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(chunk_id)
+}
+
+/// Region chunk ids and bounding boxes of each of `page_ids`
+async fn page_regions(
+    pool: &PgPool,
+    page_ids: &[i64],
+) -> Result<HashMap<i64, Vec<(i64, BoundingBox)>>> {
+    let rows: Vec<(i64, i64, Json<BoundingBox>)> = sqlx::query_as(
+        r#"
+        SELECT parent_page, id, chunk_metadata->'bbox'
+        FROM image_chunk
+        WHERE parent_page = ANY($1)
+          AND chunk_metadata->>'kind' = 'region'
+          AND chunk_metadata ? 'bbox'
+        ORDER BY id
+        "#,
+    )
+    .bind(page_ids)
+    .fetch_all(pool)
+    .await?;
+
+    let mut regions: HashMap<i64, Vec<(i64, BoundingBox)>> = HashMap::new();
+    for (page_id, chunk_id, Json(bbox)) in rows {
+        regions.entry(page_id).or_default().push((chunk_id, bbox));
+    }
+    Ok(regions)
+}
+
+/// Crop region chunks out of a new page image at their bounding boxes
+fn crop_regions(
+    img: &DynamicImage,
+    mimetype: &str,
+    regions: Vec<(i64, BoundingBox)>,
+) -> Result<Vec<(i64, CroppedRegion)>> {
+    regions
+        .into_iter()
+        .map(|(chunk_id, bbox)| Ok((chunk_id, crop_image(img, mimetype, &bbox)?)))
+        .collect()
+}
+
+/// Store regions cropped by `crop_regions` in place of their previous
+/// contents. Returns the number of chunks updated.
+async fn update_region_crops(
+    conn: &mut PgConnection,
+    crops: Vec<(i64, CroppedRegion)>,
+    source_image: &ImageInfo,
+) -> Result<i32> {
+    let count = crops.len() as i32;
+    for (chunk_id, crop) in crops {
+        sqlx::query(
+            r#"
+            UPDATE image_chunk
+            SET contents = $2, mimetype = $3,
+                chunk_metadata = chunk_metadata
+                    || jsonb_build_object('pixel_bbox', $4::jsonb, 'image', $5::jsonb)
+            WHERE id = $1
+            "#,
+        )
+        .bind(chunk_id)
+        .bind(&crop.bytes)
+        .bind(crop.mimetype)
+        .bind(serde_json::json!(crop.rect))
+        .bind(serde_json::json!(crop.image_info(Some(source_image))))
+        .execute(&mut *conn)
         .await?;
     }
+    Ok(count)
+}
 
-    tx.commit().await?;
+/// File id, path and recorded SHA-256 of the source of a document imported
+/// from a PDF or office document
+async fn pdf_document_source(
+    pool: &PgPool,
+    document_id: i64,
+) -> Result<(i64, String, Option<String>)> {
+    let source: (Option<i64>, Option<String>, Option<String>) = sqlx::query_as(
+        r#"
+        SELECT f.id, f.path, d.doc_metadata->>'source_sha256'
+        FROM document d
+        LEFT JOIN file f ON d.path = f.id
+        WHERE d.id = $1
+        "#,
+    )
+    .bind(document_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Document {} not found", document_id)))?;
+
+    match source {
+        (Some(file_id), Some(file_path), source_sha256) => Ok((file_id, file_path, source_sha256)),
+        // Image documents keep their sources per page
+        _ => Err(AppError::Custom(format!(
+            "Document {} was not imported from a PDF",
+            document_id
+        ))),
+    }
+}
+
+/// A document's source file as it was imported: the file at `path` while
+/// it matches the recorded hash, or else its copy in the content store
+async fn source_as_imported(
+    app_handle: &AppHandle,
+    pool: &PgPool,
+    path: &Path,
+    source_sha256: Option<String>,
+) -> Result<PathBuf> {
+    let exists = path.exists();
+    if exists {
+        let hash_path = path.to_path_buf();
+        let current_sha256 = spawn_blocking(move || file_sha256(&hash_path))
+            .await
+            .map_err(|e| AppError::PdfError(format!("Task join error: {}", e)))??;
+        if source_sha256
+            .as_ref()
+            .is_none_or(|sha| *sha == current_sha256)
+        {
+            return Ok(path.to_path_buf());
+        }
+    }
+
+    if let Some(sha256) = &source_sha256 {
+        let cache_dir = app_handle
+            .path()
+            .app_cache_dir()
+            .map_err(|e| AppError::Custom(format!("No cache directory: {}", e)))?;
+        if let Some(stored) = materialize_source(pool, &cache_dir, sha256, path).await? {
+            return Ok(stored);
+        }
+    }
+
+    if exists {
+        Err(AppError::SourceChanged(path.display().to_string()))
+    } else {
+        Err(AppError::NotFound(format!(
+            "{} not found, and the content store has no copy",
+            path.display()
+        )))
+    }
+}
+
+/// Copy image files into the content store, skipping content already there
+async fn store_image_sources(
+    conn: &mut PgConnection,
+    file_paths: &[String],
+    file_hashes: &[String],
+) -> Result<()> {
+    for (file_path, file_hash) in file_paths.iter().zip(file_hashes) {
+        store_source(&mut *conn, Path::new(file_path), file_hash).await?;
+    }
+    Ok(())
+}
+
+/// Reject render and chunking options that cannot produce pages
+fn validate_pdf_options(options: &IngestOptions) -> Result<()> {
+    if options.render.dpi == 0 {
+        return Err(AppError::PdfError(
+            "Render DPI must be greater than 0".to_string(),
+        ));
+    }
+    options.chunking.validate()
+}
+
+/// Ingest options matching how a document's pages were stored: the render
+/// options of its first page and its recorded chunking strategy
+async fn document_ingest_options(pool: &PgPool, document_id: i64) -> Result<IngestOptions> {
+    let (chunking, render): (Option<serde_json::Value>, Option<serde_json::Value>) =
+        sqlx::query_as(
+            r#"
+            SELECT d.doc_metadata->'chunking',
+                   (SELECT p.page_metadata->'render' FROM page p
+                    WHERE p.document_id = d.id
+                    ORDER BY p.page_num LIMIT 1)
+            FROM document d
+            WHERE d.id = $1
+            "#,
+        )
+        .bind(document_id)
+        .fetch_one(pool)
+        .await?;
+
+    let mut options = IngestOptions::default();
+    if let Some(render) = render.and_then(|render| serde_json::from_value(render).ok()) {
+        options.render = render;
+    }
+    if let Some(chunking) = chunking.and_then(|chunking| serde_json::from_value(chunking).ok()) {
+        options.extract_text = true;
+        options.chunking = chunking;
+    }
+    Ok(options)
+}
 
-    let _ = app_handle.emit(
-        "ingestion-progress",
-        IngestionProgress::complete(page_count),
+/// Convert an office document to a temporary PDF; None for PDFs.
+/// The original file stays the recorded and hashed source; the returned
+/// value keeps the temporary PDF alive until rendering is done.
+async fn convert_office_document(
+    app_handle: &AppHandle,
+    job: &IngestionJob,
+    path: &Path,
+) -> Result<Option<ConvertedPdf>> {
+    if office_extension(path).is_none() {
+        return Ok(None);
+    }
+
+    job.report(
+        app_handle,
+        IngestionProgress::converting(&path.to_string_lossy()),
     );
+    let (convert_path, convert_job) = (path.to_path_buf(), job.clone());
+    let converted =
+        spawn_blocking(move || convert_to_pdf(&convert_path, || convert_job.is_cancelled()))
+            .await
+            .map_err(|e| AppError::PdfError(format!("Task join error: {}", e)))?;
+    // A cancelled conversion fails; report it as the cancellation it is
+    job.ensure_active(app_handle)?;
+    Ok(Some(converted?))
+}
+
+/// Pages of an opened PDF to store in a document
+struct PdfPages {
+    document_id: i64,
+    /// Source path recorded in `page_metadata.source_path`
+    file_path: String,
+    /// PDF to render, the converted one for office documents
+    pdf_path: PathBuf,
+    password: Option<String>,
+    /// Physical page numbers to store, in ascending order
+    page_nums: Vec<i32>,
+    /// Logical labels of all pages of the PDF, empty if it defines none
+    page_labels: Vec<String>,
+}
+
+/// What `store_pdf_pages` inserted
+struct StoredPages {
+    page_count: i32,
+    image_chunk_count: i32,
+    chunk_count: i32,
+    blank_pages: Vec<i32>,
+}
+
+/// Render `pages` and insert each with its image, region, thumbnail and
+/// text chunks, in page order as the renderer delivers them
+async fn store_pdf_pages(
+    tx: &mut PgConnection,
+    app_handle: &AppHandle,
+    job: &IngestionJob,
+    pages: PdfPages,
+    options: IngestOptions,
+) -> Result<StoredPages> {
+    let PdfPages {
+        document_id,
+        file_path,
+        pdf_path,
+        password,
+        page_nums,
+        page_labels,
+    } = pages;
+
+    let mimetype = options.render.format.mimetype();
+    let render_metadata = serde_json::to_value(&options.render)
+        .map_err(|e| AppError::Custom(format!("Failed to serialize render options: {}", e)))?;
+
+    let chunking = options.chunking.clone();
+    let blank_policy = options.blank_pages;
+    let total_pages = page_nums.len() as i32;
+    let mut stored = StoredPages {
+        page_count: 0,
+        image_chunk_count: 0,
+        chunk_count: 0,
+        blank_pages: Vec::new(),
+    };
+
+    // Dropping the receiver on error or cancellation stops the renderer;
+    // dropping the transaction rolls back the pages inserted so far.
+    let mut rendered_pages = stream_pages(pdf_path, password, page_nums, options);
+    let mut rendered_count = 0;
+    while let Some(rendered) = rendered_pages.recv().await {
+        job.ensure_active(app_handle)?;
+        let rendered = rendered?;
+        rendered_count += 1;
+        job.report(
+            app_handle,
+            IngestionProgress::rendering(rendered_count, total_pages),
+        );
+
+        let mut page_metadata = serde_json::json!({
+            "source_path": file_path,
+            "render": render_metadata,
+        });
+        if let Some(label) = page_labels.get(rendered.page_num as usize - 1) {
+            page_metadata["page_label"] = label.as_str().into();
+        }
+        if let Some(fingerprint) = &rendered.fingerprint {
+            if fingerprint.blank && blank_policy != BlankPagePolicy::Keep {
+                stored.blank_pages.push(rendered.page_num);
+                if blank_policy == BlankPagePolicy::Skip {
+                    continue;
+                }
+            }
+            page_metadata["fingerprint"] = serde_json::json!(fingerprint);
+        }
+
+        let (page_id, page_chunk_id) = insert_page_with_chunk(
+            &mut *tx,
+            document_id,
+            rendered.page_num,
+            &rendered.bytes,
+            mimetype,
+            page_metadata,
+            &rendered.image,
+        )
+        .await?;
+        insert_thumbnails(&mut *tx, page_chunk_id, &rendered.thumbnails).await?;
+        stored.page_count += 1;
+        stored.image_chunk_count += 1;
+
+        for candidate in &rendered.regions {
+            insert_auto_region(&mut *tx, page_id, page_chunk_id, &rendered.image, candidate)
+                .await?;
+            stored.image_chunk_count += 1;
+        }
+
+        if let Some(text) = rendered.text {
+            for contents in chunking.split(&text) {
+                insert_text_chunk(&mut *tx, page_id, &contents).await?;
+                stored.chunk_count += 1;
+            }
+        }
+    }
+
+    // A renderer that died closes the channel early; fail so the caller's
+    // transaction rolls back instead of committing a partial document
+    if rendered_count < total_pages {
+        return Err(AppError::PdfError(format!(
+            "Rendering stopped after {} of {} pages",
+            rendered_count, total_pages
+        )));
+    }
+
+    Ok(stored)
+}
+
+/// `doc_metadata` for a new document: its source hash, the duplicate it
+/// was forced over, and the text chunking strategy when text is extracted
+fn document_metadata(
+    source_sha256: &str,
+    duplicate_of: Option<i64>,
+    options: &IngestOptions,
+) -> serde_json::Value {
+    let mut doc_metadata = serde_json::json!({"source_sha256": source_sha256});
+    if let Some(existing_id) = duplicate_of {
+        doc_metadata["duplicate_of"] = existing_id.into();
+    }
+    if options.extract_text {
+        doc_metadata["chunking"] = serde_json::json!(options.chunking);
+    }
+    doc_metadata
+}
+
+/// Find the earliest document ingested from a source with this hash
+async fn find_duplicate(pool: &PgPool, source_sha256: &str) -> Result<Option<i64>> {
+    let existing_id = sqlx::query_scalar(
+        r#"SELECT id FROM document WHERE doc_metadata->>'source_sha256' = $1 ORDER BY id LIMIT 1"#,
+    )
+    .bind(source_sha256)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(existing_id)
+}
+
+/// Describe an existing document as the result of a linked (skipped) import
+async fn linked_result(pool: &PgPool, document_id: i64) -> Result<IngestionResult> {
+    let (file_id, page_count, image_chunk_count, chunk_count, blank_pages): (
+        Option<i64>,
+        i64,
+        i64,
+        i64,
+        Vec<i32>,
+    ) = sqlx::query_as(
+        r#"
+            SELECT
+                d.path,
+                (SELECT COUNT(*) FROM page p WHERE p.document_id = d.id),
+                (SELECT COUNT(*) FROM image_chunk ic
+                 JOIN page p ON ic.parent_page = p.id
+                 WHERE p.document_id = d.id),
+                (SELECT COUNT(DISTINCT pcr.chunk_id) FROM page_chunk_relation pcr
+                 JOIN page p ON pcr.page_id = p.id
+                 WHERE p.document_id = d.id),
+                ARRAY(SELECT p.page_num FROM page p
+                      WHERE p.document_id = d.id
+                        AND (p.page_metadata->'fingerprint'->>'blank')::boolean
+                      ORDER BY p.page_num)
+            FROM document d
+            WHERE d.id = $1
+            "#,
+    )
+    .bind(document_id)
+    .fetch_one(pool)
+    .await?;
 
     Ok(IngestionResult {
-        file_id,
+        file_id: file_id.unwrap_or(0),
         document_id,
-        page_count,
-        image_chunk_count: page_count,
+        page_count: page_count as i32,
+        image_chunk_count: image_chunk_count as i32,
+        chunk_count: chunk_count as i32,
+        duplicate_of: Some(document_id),
+        blank_pages,
     })
 }
 
-/// Load an image file and convert it to PNG bytes
-fn load_image_as_png(path: &Path) -> Result<Vec<u8>> {
-    let img = ImageReader::open(path)
-        .map_err(|e| AppError::ImageError(format!("Failed to open image: {}", e)))?
-        .decode()
-        .map_err(|e| AppError::ImageError(format!("Failed to decode image: {}", e)))?;
+/// Insert a page row together with its full-page image chunk, recording
+/// `image` in both their metadata. Returns the new page and chunk ids.
+async fn insert_page_with_chunk(
+    conn: &mut PgConnection,
+    document_id: i64,
+    page_num: i32,
+    contents: &[u8],
+    mimetype: &str,
+    mut page_metadata: serde_json::Value,
+    image: &ImageInfo,
+) -> Result<(i64, i64)> {
+    page_metadata["image"] = serde_json::json!(image);
 
-    let mut png_bytes = Vec::new();
-    img.write_to(&mut Cursor::new(&mut png_bytes), image::ImageFormat::Png)
-        .map_err(|e| AppError::ImageError(format!("Failed to encode as PNG: {}", e)))?;
+    let page_id: i64 = sqlx::query_scalar(
+        r#"INSERT INTO page (page_num, document_id, image_contents, mimetype, page_metadata)
+           VALUES ($1, $2, NULL, $3, $4) RETURNING id"#,
+    )
+    .bind(page_num)
+    .bind(document_id)
+    .bind(mimetype)
+    .bind(&page_metadata)
+    .fetch_one(&mut *conn)
+    .await?;
 
-    Ok(png_bytes)
+    let chunk_id: i64 = sqlx::query_scalar(
+        r#"INSERT INTO image_chunk (parent_page, contents, mimetype, chunk_metadata)
+           VALUES ($1, $2, $3, $4) RETURNING id"#,
+    )
+    .bind(page_id)
+    .bind(contents)
+    .bind(mimetype)
+    .bind(serde_json::json!({"kind": "page", "image": image}))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok((page_id, chunk_id))
 }
 
-/// Ingest multiple image files into the database as a single document
+/// Insert an automatically detected region as a pending region chunk
+async fn insert_auto_region(
+    conn: &mut PgConnection,
+    page_id: i64,
+    source_chunk_id: i64,
+    source_image: &ImageInfo,
+    candidate: &RegionCandidate,
+) -> Result<i64> {
+    let chunk_metadata = serde_json::json!({
+        "kind": "region",
+        "source": "auto",
+        "status": "pending",
+        "label": candidate.region.label,
+        "bbox": candidate.region.bbox,
+        "pixel_bbox": candidate.crop.rect,
+        "ink_coverage": candidate.region.ink_coverage,
+        "source_chunk_id": source_chunk_id,
+        "image": candidate.crop.image_info(Some(source_image)),
+    });
+
+    let chunk_id: i64 = sqlx::query_scalar(
+        r#"INSERT INTO image_chunk (parent_page, contents, mimetype, chunk_metadata)
+           VALUES ($1, $2, $3, $4) RETURNING id"#,
+    )
+    .bind(page_id)
+    .bind(&candidate.crop.bytes)
+    .bind(candidate.crop.mimetype)
+    .bind(&chunk_metadata)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    Ok(chunk_id)
+}
+
+/// Insert a text chunk and link it to its page through `page_chunk_relation`.
+/// Returns the new chunk id.
+async fn insert_text_chunk(conn: &mut PgConnection, page_id: i64, contents: &str) -> Result<i64> {
+    let chunk_id: i64 =
+        sqlx::query_scalar(r#"INSERT INTO chunk (contents) VALUES ($1) RETURNING id"#)
+            .bind(contents)
+            .fetch_one(&mut *conn)
+            .await?;
+
+    sqlx::query(r#"INSERT INTO page_chunk_relation (page_id, chunk_id) VALUES ($1, $2)"#)
+        .bind(page_id)
+        .bind(chunk_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(chunk_id)
+}
+
+/// Import externally produced text (e.g. OCR output) for a page, split into
+/// chunks with the given strategy. Existing chunks of the page are kept.
+#[tauri::command]
+pub async fn import_page_text(
+    page_id: i64,
+    text: String,
+    chunking: Option<ChunkingStrategy>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Chunk>> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+    let chunking = chunking.unwrap_or_default();
+    chunking.validate()?;
+
+    let document_id: i64 = sqlx::query_scalar(r#"SELECT document_id FROM page WHERE id = $1"#)
+        .bind(page_id)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Page {} not found", page_id)))?;
+
+    let mut tx = pool.begin().await?;
+
+    let mut chunk_ids = Vec::new();
+    for contents in chunking.split(&text) {
+        chunk_ids.push(insert_text_chunk(&mut tx, page_id, &contents).await?);
+    }
+
+    sqlx::query(
+        r#"UPDATE document
+           SET doc_metadata = COALESCE(doc_metadata, '{}'::jsonb) || jsonb_build_object('chunking', $2::jsonb)
+           WHERE id = $1"#,
+    )
+    .bind(document_id)
+    .bind(serde_json::json!(chunking))
+    .execute(&mut *tx)
+    .await?;
+
+    let chunks = sqlx::query_as::<_, Chunk>(
+        r#"SELECT id, contents, is_table, table_type FROM chunk WHERE id = ANY($1) ORDER BY id"#,
+    )
+    .bind(&chunk_ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(chunks)
+}
+
+/// One page of an image document, encoded for storage
+struct LoadedImage {
+    page_num: i32,
+    file_index: usize,
+    /// 1-based page within a multi-page TIFF
+    frame: Option<usize>,
+    bytes: Vec<u8>,
+    mimetype: &'static str,
+    image: ImageInfo,
+    source: SourceImageInfo,
+    fingerprint: Option<PageFingerprint>,
+    thumbnails: Vec<Thumbnail>,
+}
+
+/// Ingest multiple image files into the database as a single document.
+/// Every page of a multi-page TIFF becomes its own page. Pages are rotated
+/// upright from their EXIF orientation and converted to sRGB; the original
+/// dimensions and orientation are kept in `page_metadata.original`.
+/// The document is identified by the hashes of its files, in order;
+/// of the options, `on_duplicate`, `thumbnail_sizes`,
+/// `preserve_original_bytes`, `fingerprint`, `blank_pages`,
+/// `blank_threshold` and `store_sources` apply to images.
+/// Runs as a cancellable job like `ingest_pdf`.
 #[tauri::command]
 pub async fn ingest_images(
     file_paths: Vec<String>,
     title: String,
+    options: Option<IngestOptions>,
     app_handle: AppHandle,
     state: State<'_, AppState>,
+) -> Result<IngestionResult> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+    let job = state.jobs.start("images", &title);
+
+    let result = import_images(
+        &pool,
+        &app_handle,
+        &job,
+        file_paths,
+        title,
+        options.unwrap_or_default(),
+    )
+    .await?;
+
+    job.report(&app_handle, IngestionProgress::complete(result.page_count));
+    Ok(result)
+}
+
+/// Ingest image files as one document as part of `job`,
+/// shared by `ingest_images` and `ingest_directory`; see `import_pdf`
+async fn import_images(
+    pool: &PgPool,
+    app_handle: &AppHandle,
+    job: &IngestionJob,
+    file_paths: Vec<String>,
+    title: String,
+    options: IngestOptions,
 ) -> Result<IngestionResult> {
     if file_paths.is_empty() {
         return Err(AppError::ImageError("No files provided".to_string()));
@@ -129,15 +1112,245 @@ pub async fn ingest_images(
         return Err(AppError::ImageError("Title is required".to_string()));
     }
 
+    let total_images = file_paths.len() as i32;
+
+    job.report(app_handle, IngestionProgress::reading(total_images));
+
+    let (paths, file_hashes) = hash_image_files(&file_paths).await?;
+    let source_sha256 = combined_sha256(&file_hashes);
+
+    let duplicate_of = find_duplicate(pool, &source_sha256).await?;
+    if let Some(existing_id) = duplicate_of {
+        match options.on_duplicate {
+            DuplicatePolicy::Refuse => return Err(AppError::DuplicateDocument(existing_id)),
+            DuplicatePolicy::Link => return linked_result(pool, existing_id).await,
+            DuplicatePolicy::Force => {}
+        }
+    }
+
+    job.ensure_active(app_handle)?;
+
+    let (load_job, load_app_handle, load_options) =
+        (job.clone(), app_handle.clone(), options.clone());
+    let (image_data, blank_pages) =
+        spawn_blocking(move || load_images(&paths, 1, &load_options, &load_job, &load_app_handle))
+            .await
+            .map_err(|e| AppError::ImageError(format!("Task join error: {}", e)))??;
+
+    job.ensure_active(app_handle)?;
+
+    let mut tx = pool.begin().await?;
+
+    let doc_metadata = document_metadata(&source_sha256, duplicate_of, &options);
+
+    let document_id: i64 = sqlx::query_scalar(
+        r#"INSERT INTO document (path, filename, title, doc_metadata)
+           VALUES (NULL, NULL, $1, $2) RETURNING id"#,
+    )
+    .bind(&title)
+    .bind(&doc_metadata)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    let page_count = image_data.len() as i32;
+    insert_images(&mut tx, document_id, image_data, &file_paths, &file_hashes).await?;
+    if options.store_sources {
+        store_image_sources(&mut tx, &file_paths, &file_hashes).await?;
+    }
+
+    job.ensure_active(app_handle)?;
+    tx.commit().await?;
+
+    Ok(IngestionResult {
+        file_id: 0,
+        document_id,
+        page_count,
+        image_chunk_count: page_count,
+        chunk_count: 0,
+        duplicate_of,
+        blank_pages,
+    })
+}
+
+/// Append image files to an existing image document as new pages after
+/// its last page. Options apply as in `ingest_images`, except that
+/// `on_duplicate` is ignored. Runs as a cancellable job like `ingest_images`.
+#[tauri::command]
+pub async fn append_images(
+    document_id: i64,
+    file_paths: Vec<String>,
+    options: Option<IngestOptions>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<IngestionResult> {
     let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+    let options = options.unwrap_or_default();
 
-    let total_images = file_paths.len() as i32;
+    if file_paths.is_empty() {
+        return Err(AppError::ImageError("No files provided".to_string()));
+    }
+    ensure_image_document(&pool, document_id).await?;
 
-    let _ = app_handle.emit(
-        "ingestion-progress",
-        IngestionProgress::reading(total_images),
+    let job = state
+        .jobs
+        .start("images", &format!("document {}", document_id));
+    job.report(
+        &app_handle,
+        IngestionProgress::reading(file_paths.len() as i32),
     );
 
+    let (paths, file_hashes) = hash_image_files(&file_paths).await?;
+    let first_page_num: i32 = sqlx::query_scalar(
+        r#"SELECT COALESCE(MAX(page_num), 0) + 1 FROM page WHERE document_id = $1"#,
+    )
+    .bind(document_id)
+    .fetch_one(&pool)
+    .await?;
+
+    job.ensure_active(&app_handle)?;
+
+    let store_sources = options.store_sources;
+    let (load_job, load_app_handle) = (job.clone(), app_handle.clone());
+    let (image_data, blank_pages) = spawn_blocking(move || {
+        load_images(
+            &paths,
+            first_page_num,
+            &options,
+            &load_job,
+            &load_app_handle,
+        )
+    })
+    .await
+    .map_err(|e| AppError::ImageError(format!("Task join error: {}", e)))??;
+
+    job.ensure_active(&app_handle)?;
+
+    let mut tx = pool.begin().await?;
+
+    let page_count = image_data.len() as i32;
+    insert_images(&mut tx, document_id, image_data, &file_paths, &file_hashes).await?;
+    if store_sources {
+        store_image_sources(&mut tx, &file_paths, &file_hashes).await?;
+    }
+    refresh_image_document_hash(&mut tx, document_id).await?;
+
+    job.ensure_active(&app_handle)?;
+    tx.commit().await?;
+
+    job.report(&app_handle, IngestionProgress::complete(page_count));
+
+    Ok(IngestionResult {
+        file_id: 0,
+        document_id,
+        page_count,
+        image_chunk_count: page_count,
+        chunk_count: 0,
+        duplicate_of: None,
+        blank_pages,
+    })
+}
+
+/// Replace the image of a page in an image document with an image file.
+/// The page keeps its number and its chunks keep their ids, so evidence
+/// pointing at the page stays valid; region chunks are cropped again from
+/// their `bbox` and text chunks are kept. The file must hold a single
+/// image. Of `options`, the thumbnail, original bytes and fingerprint
+/// settings apply; the page is never skipped as blank.
+#[tauri::command]
+pub async fn replace_page_image(
+    page_id: i64,
+    file_path: String,
+    options: Option<IngestOptions>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<PageInfo> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+    let options = IngestOptions {
+        blank_pages: BlankPagePolicy::Keep,
+        ..options.unwrap_or_default()
+    };
+
+    let (document_id, page_num): (i64, i32) =
+        sqlx::query_as(r#"SELECT document_id, page_num FROM page WHERE id = $1"#)
+            .bind(page_id)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Page {} not found", page_id)))?;
+    ensure_image_document(&pool, document_id).await?;
+
+    let job = state.jobs.start("images", &file_path);
+    job.report(&app_handle, IngestionProgress::reading(1));
+
+    let (paths, file_hashes) = hash_image_files(std::slice::from_ref(&file_path)).await?;
+    let boxes = page_regions(&pool, &[page_id])
+        .await?
+        .remove(&page_id)
+        .unwrap_or_default();
+
+    let store_sources = options.store_sources;
+    let (load_job, load_app_handle) = (job.clone(), app_handle.clone());
+    let (images, crops) = spawn_blocking(move || {
+        let (images, _) = load_images(&paths, page_num, &options, &load_job, &load_app_handle)?;
+        let crops = match images.as_slice() {
+            [image] => crop_regions(&decode_image(&image.bytes)?, image.mimetype, boxes)?,
+            _ => Vec::new(),
+        };
+        Ok::<_, AppError>((images, crops))
+    })
+    .await
+    .map_err(|e| AppError::ImageError(format!("Task join error: {}", e)))??;
+
+    job.ensure_active(&app_handle)?;
+
+    let [image] = <[LoadedImage; 1]>::try_from(images).map_err(|images| {
+        AppError::ImageError(format!(
+            "{} has {} pages; a page can only be replaced by a single image",
+            file_path,
+            images.len()
+        ))
+    })?;
+
+    let mut tx = pool.begin().await?;
+
+    let page_metadata = image_page_metadata(&image, &file_path, &file_hashes[0]);
+    let chunk_id = replace_page_contents(
+        &mut tx,
+        page_id,
+        &image.bytes,
+        image.mimetype,
+        page_metadata,
+        &image.image,
+    )
+    .await?;
+    insert_thumbnails(&mut tx, chunk_id, &image.thumbnails).await?;
+    update_region_crops(&mut tx, crops, &image.image).await?;
+    if store_sources {
+        store_source(&mut tx, Path::new(&file_path), &file_hashes[0]).await?;
+    }
+    refresh_image_document_hash(&mut tx, document_id).await?;
+
+    let page = sqlx::query_as::<_, PageInfo>(
+        r#"
+        SELECT id, page_num, document_id, mimetype, page_metadata, page_metadata->'image' AS image,
+               page_metadata->>'page_label' AS page_label
+        FROM page
+        WHERE id = $1
+        "#,
+    )
+    .bind(page_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    job.ensure_active(&app_handle)?;
+    tx.commit().await?;
+
+    job.report(&app_handle, IngestionProgress::complete(1));
+
+    Ok(page)
+}
+
+/// Check that image files exist and hash them, in order
+async fn hash_image_files(file_paths: &[String]) -> Result<(Vec<PathBuf>, Vec<String>)> {
     let paths: Vec<PathBuf> = file_paths.iter().map(PathBuf::from).collect();
     for path in &paths {
         if !path.exists() {
@@ -148,75 +1361,228 @@ pub async fn ingest_images(
         }
     }
 
-    let app_handle_clone = app_handle.clone();
-    let image_data: Vec<Vec<u8>> = spawn_blocking(move || {
-        let mut results = Vec::with_capacity(paths.len());
-        for (idx, path) in paths.iter().enumerate() {
-            let _ = app_handle_clone.emit(
-                "ingestion-progress",
-                IngestionProgress::rendering((idx + 1) as i32, total_images),
-            );
-
-            let png_bytes = load_image_as_png(path)?;
-            results.push(png_bytes);
-        }
-        Ok::<_, AppError>(results)
+    let hash_paths = paths.clone();
+    let file_hashes = spawn_blocking(move || {
+        hash_paths
+            .iter()
+            .map(|p| file_sha256(p))
+            .collect::<Result<Vec<String>>>()
     })
     .await
     .map_err(|e| AppError::ImageError(format!("Task join error: {}", e)))??;
 
-    let mut tx = pool.begin().await?;
+    Ok((paths, file_hashes))
+}
 
-    let document_id: i64 = sqlx::query_scalar(
-        r#"INSERT INTO document (path, filename, title) VALUES (NULL, NULL, $1) RETURNING id"#,
-    )
-    .bind(&title)
-    .fetch_one(&mut *tx)
-    .await?;
+/// Decode, normalize and encode image files as pages numbered from
+/// `first_page_num`, reporting progress to `job`. Returns the pages to
+/// store and the page numbers detected as blank. Stops early, returning
+/// what was loaded, when the job is cancelled.
+fn load_images(
+    paths: &[PathBuf],
+    first_page_num: i32,
+    options: &IngestOptions,
+    job: &IngestionJob,
+    app_handle: &AppHandle,
+) -> Result<(Vec<LoadedImage>, Vec<i32>)> {
+    let total_images = paths.len() as i32;
+    let fingerprint_pages = options.fingerprint_pages();
+    let (blank_policy, blank_threshold) = (options.blank_pages, options.blank_threshold);
 
-    let mimetype = "image/png".to_string();
+    let mut results = Vec::with_capacity(paths.len());
+    let mut blank_pages = Vec::new();
+    let mut page_num = first_page_num - 1;
+    for (idx, path) in paths.iter().enumerate() {
+        if job.is_cancelled() {
+            break;
+        }
+        job.report(
+            app_handle,
+            IngestionProgress::rendering((idx + 1) as i32, total_images),
+        );
 
-    for (page_idx, png_bytes) in image_data.into_iter().enumerate() {
-        let page_metadata = serde_json::json!({"source_path": file_paths[page_idx]});
-        let page_id: i64 = sqlx::query_scalar(
-            r#"INSERT INTO page (page_num, document_id, image_contents, mimetype, page_metadata)
-               VALUES ($1, $2, NULL, $3, $4) RETURNING id"#,
-        )
-        .bind((page_idx + 1) as i32)
-        .bind(document_id)
-        .bind(&mimetype)
-        .bind(&page_metadata)
-        .fetch_one(&mut *tx)
-        .await?;
+        let frames = load_image_frames(path)?;
+        let multi_page = frames.len() > 1;
+
+        for (frame_idx, frame) in frames.into_iter().enumerate() {
+            page_num += 1;
+            let fingerprint =
+                fingerprint_pages.then(|| fingerprint_page(&frame.image, blank_threshold));
+            if fingerprint.as_ref().is_some_and(|f| f.blank)
+                && blank_policy != BlankPagePolicy::Keep
+            {
+                blank_pages.push(page_num);
+                if blank_policy == BlankPagePolicy::Skip {
+                    continue;
+                }
+            }
+
+            // Single-page files browsers can display are stored as they
+            // are, unless normalization changed their pixels
+            let original = if options.preserve_original_bytes
+                && !multi_page
+                && !frame.source.is_normalized()
+            {
+                let bytes = std::fs::read(path)?;
+                browser_mimetype(&bytes).map(|mimetype| (bytes, mimetype))
+            } else {
+                None
+            };
+
+            let (bytes, mimetype, renderer) = match original {
+                Some((bytes, mimetype)) => (bytes, mimetype, None),
+                None => {
+                    let (bytes, mimetype) = encode_image(&frame.image, "image/png")?;
+                    (bytes, mimetype, Some(IMAGE_RENDERER))
+                }
+            };
+            let dimensions = (frame.image.width(), frame.image.height());
+            results.push(LoadedImage {
+                page_num,
+                file_index: idx,
+                frame: multi_page.then_some(frame_idx + 1),
+                image: image_info(&bytes, dimensions, frame.source.dpi, renderer),
+                bytes,
+                mimetype,
+                thumbnails: make_thumbnails(&frame.image, &options.thumbnail_sizes)?,
+                source: frame.source,
+                fingerprint,
+            });
+        }
+    }
+
+    Ok((results, blank_pages))
+}
+
+/// `page_metadata` of a page loaded from the image file `file_path`
+fn image_page_metadata(image: &LoadedImage, file_path: &str, file_hash: &str) -> serde_json::Value {
+    let mut page_metadata = serde_json::json!({
+        "source_path": file_path,
+        "source_sha256": file_hash,
+        "original": image.source,
+    });
+    if let Some(frame) = image.frame {
+        page_metadata["frame"] = frame.into();
+    }
+    if let Some(fingerprint) = &image.fingerprint {
+        page_metadata["fingerprint"] = serde_json::json!(fingerprint);
+    }
+    page_metadata
+}
 
-        sqlx::query_scalar::<_, i64>(
-            r#"INSERT INTO image_chunk (parent_page, contents, mimetype)
-               VALUES ($1, $2, $3) RETURNING id"#,
+/// Insert loaded images as pages of `document_id` with their thumbnails
+async fn insert_images(
+    conn: &mut PgConnection,
+    document_id: i64,
+    images: Vec<LoadedImage>,
+    file_paths: &[String],
+    file_hashes: &[String],
+) -> Result<()> {
+    for image in images {
+        let page_metadata = image_page_metadata(
+            &image,
+            &file_paths[image.file_index],
+            &file_hashes[image.file_index],
+        );
+        let (_, chunk_id) = insert_page_with_chunk(
+            &mut *conn,
+            document_id,
+            image.page_num,
+            &image.bytes,
+            image.mimetype,
+            page_metadata,
+            &image.image,
         )
-        .bind(page_id)
-        .bind(&png_bytes)
-        .bind(&mimetype)
-        .fetch_one(&mut *tx)
         .await?;
+        insert_thumbnails(&mut *conn, chunk_id, &image.thumbnails).await?;
     }
 
-    tx.commit().await?;
+    Ok(())
+}
 
-    let _ = app_handle.emit(
-        "ingestion-progress",
-        IngestionProgress::complete(total_images),
-    );
+/// Ingest every PDF and image group found in a directory.
+/// Each item is committed on its own, so a file that fails is reported and
+/// the batch moves on; duplicates are reported as skipped. Cancelling the
+/// job rolls back the item in progress and stops the batch.
+#[tauri::command]
+pub async fn ingest_directory(
+    directory: String,
+    scan: Option<DirectoryScanOptions>,
+    options: Option<IngestOptions>,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<DirectoryIngestionReport> {
+    let pool = state.get_pool().await.ok_or(AppError::NotConnected)?;
+    let scan = scan.unwrap_or_default();
+    let options = options.unwrap_or_default();
 
-    Ok(IngestionResult {
-        file_id: 0,
-        document_id,
-        page_count: total_images,
-        image_chunk_count: total_images,
-    })
+    let root = PathBuf::from(&directory);
+    let targets = spawn_blocking(move || scan_directory(&root, &scan))
+        .await
+        .map_err(|e| AppError::Custom(format!("Task join error: {}", e)))??;
+
+    let job = state.jobs.start("directory", &directory);
+    let mut report = DirectoryIngestionReport::new(directory);
+    let total_items = targets.len() as i32;
+
+    for (index, target) in targets.iter().enumerate() {
+        let outcome = match target {
+            IngestTarget::Pdf(_) | IngestTarget::Office(_) => {
+                let file_path = target.files().remove(0);
+                let source = PdfSource::new(file_path);
+                import_pdf(&pool, &app_handle, &job, source, options.clone()).await
+            }
+            IngestTarget::Images { title, .. } => {
+                import_images(
+                    &pool,
+                    &app_handle,
+                    &job,
+                    target.files(),
+                    title.clone(),
+                    options.clone(),
+                )
+                .await
+            }
+        };
+
+        let cancelled = matches!(outcome, Err(AppError::Cancelled(_)));
+        report.record(target, outcome);
+        if cancelled {
+            report.cancelled = true;
+            break;
+        }
+        job.report(
+            &app_handle,
+            IngestionProgress::batch_item(index as i32 + 1, total_items),
+        );
+    }
+
+    if !report.cancelled {
+        job.report(&app_handle, IngestionProgress::batch_complete(total_items));
+    }
+
+    Ok(report)
+}
+
+/// Request cancellation of a running ingestion job.
+/// Returns false if the job already finished or does not exist.
+#[tauri::command]
+pub fn cancel_ingestion(job_id: u64, state: State<'_, AppState>) -> bool {
+    state.jobs.cancel(job_id)
+}
+
+/// List running ingestion jobs with their latest progress
+#[tauri::command]
+pub fn list_ingestion_jobs(state: State<'_, AppState>) -> Vec<IngestionJobInfo> {
+    state.jobs.list()
 }
 
 /// Get supported file formats for ingestion
 #[tauri::command]
-pub fn get_supported_formats() -> Vec<&'static str> {
-    vec!["pdf", "png", "jpg", "jpeg", "webp"]
+pub fn get_supported_formats() -> SupportedFormats {
+    SupportedFormats {
+        pdf: vec!["pdf"],
+        office: OFFICE_EXTENSIONS.to_vec(),
+        images: image_extensions(),
+    }
 }
@@ -52,7 +52,7 @@ pub async fn create_query(
             // Fetch chunk and page info
             let chunk = sqlx::query_as::<_, ImageChunkInfo>(
                 r#"
-                SELECT id, parent_page, mimetype
+                SELECT id, parent_page, mimetype, chunk_metadata, chunk_metadata->'image' AS image
                 FROM image_chunk
                 WHERE id = $1
                 "#,
@@ -65,7 +65,8 @@ pub async fn create_query(
                 if let Some(parent_page) = c.parent_page {
                     sqlx::query_as::<_, PageInfo>(
                         r#"
-                        SELECT id, page_num, document_id, mimetype, page_metadata
+                        SELECT id, page_num, document_id, mimetype, page_metadata, page_metadata->'image' AS image,
+                               page_metadata->>'page_label' AS page_label
                         FROM page
                         WHERE id = $1
                         "#,
@@ -214,7 +215,7 @@ pub async fn get_query_with_evidence(
         let chunk = if let Some(chunk_id) = relation.image_chunk_id {
             sqlx::query_as::<_, ImageChunkInfo>(
                 r#"
-                SELECT id, parent_page, mimetype
+                SELECT id, parent_page, mimetype, chunk_metadata, chunk_metadata->'image' AS image
                 FROM image_chunk
                 WHERE id = $1
                 "#,
@@ -231,7 +232,8 @@ pub async fn get_query_with_evidence(
             if let Some(parent_page) = c.parent_page {
                 sqlx::query_as::<_, PageInfo>(
                     r#"
-                    SELECT id, page_num, document_id, mimetype, page_metadata
+                    SELECT id, page_num, document_id, mimetype, page_metadata, page_metadata->'image' AS image,
+                           page_metadata->>'page_label' AS page_label
                     FROM page
                     WHERE id = $1
                     "#,
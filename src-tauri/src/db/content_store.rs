@@ -0,0 +1,220 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use sqlx::{Connection, PgConnection, PgPool};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::task::spawn_blocking;
+
+use crate::error::{AppError, Result};
+use crate::ingest::file_sha256;
+
+/// Sources are stored as rows of `source_blob_chunk` of at most this many bytes
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Largest source file the content store accepts
+const MAX_SOURCE_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// Directory under `cache_dir` where stored sources are written out to be
+/// opened locally. It is created private to the user; an existing entry is
+/// used only if it is a real directory that can be made private again,
+/// which fails unless the user owns it.
+fn materialized_dir(cache_dir: &Path) -> Result<PathBuf> {
+    let dir = cache_dir.join("sources");
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(&dir)?;
+
+    let metadata = std::fs::symlink_metadata(&dir)?;
+    if !metadata.is_dir() {
+        return Err(AppError::Custom(format!(
+            "{} is not a directory",
+            dir.display()
+        )));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o077 != 0 {
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+    }
+
+    Ok(dir)
+}
+
+/// Copy the file at `path` into the content store under its SHA-256, which
+/// must equal `sha256`. The file is streamed into `source_blob_chunk` rows,
+/// in a transaction so a failed copy leaves nothing behind. Files larger
+/// than `MAX_SOURCE_SIZE` are rejected. Returns false if the content was
+/// already stored.
+pub async fn store_source(conn: &mut PgConnection, path: &Path, sha256: &str) -> Result<bool> {
+    let stored: bool =
+        sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM source_blob WHERE sha256 = $1)"#)
+            .bind(sha256)
+            .fetch_one(&mut *conn)
+            .await?;
+    if stored {
+        return Ok(false);
+    }
+
+    let mut file = tokio::fs::File::open(path).await?;
+    let byte_size = file.metadata().await?.len();
+    if byte_size > MAX_SOURCE_SIZE {
+        return Err(AppError::SourceTooLarge(path.display().to_string()));
+    }
+
+    let mut tx = conn.begin().await?;
+    let result = sqlx::query(
+        r#"INSERT INTO source_blob (sha256, byte_size)
+           VALUES ($1, $2)
+           ON CONFLICT (sha256) DO NOTHING"#,
+    )
+    .bind(sha256)
+    .bind(byte_size as i64)
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    let mut hasher = Sha256::new();
+    let mut total: u64 = 0;
+    let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+    for seq in 0i32.. {
+        chunk.clear();
+        (&mut file)
+            .take(CHUNK_SIZE as u64)
+            .read_to_end(&mut chunk)
+            .await?;
+        if chunk.is_empty() && seq > 0 {
+            break;
+        }
+        total += chunk.len() as u64;
+        if total > byte_size {
+            return Err(AppError::SourceChanged(path.display().to_string()));
+        }
+        hasher.update(&chunk);
+
+        sqlx::query(
+            r#"INSERT INTO source_blob_chunk (sha256, seq, data)
+               VALUES ($1, $2, $3)"#,
+        )
+        .bind(sha256)
+        .bind(seq)
+        .bind(&chunk)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    if total != byte_size || format!("{:x}", hasher.finalize()) != sha256 {
+        return Err(AppError::SourceChanged(path.display().to_string()));
+    }
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// Write the stored source with hash `sha256` to a file under the app's
+/// `cache_dir`, named after its hash and the extension of `original_path`.
+/// An earlier copy is reused only if it is a regular file that still hashes
+/// to `sha256`. Returns None when the content store does not hold it.
+pub async fn materialize_source(
+    pool: &PgPool,
+    cache_dir: &Path,
+    sha256: &str,
+    original_path: &Path,
+) -> Result<Option<PathBuf>> {
+    let mut file_name = sha256.to_string();
+    if let Some(extension) = original_path.extension() {
+        file_name.push('.');
+        file_name.push_str(&extension.to_string_lossy());
+    }
+
+    let dir_parent = cache_dir.to_path_buf();
+    let expected_sha256 = sha256.to_string();
+    let (local_path, reusable) = spawn_blocking(move || -> Result<(PathBuf, bool)> {
+        let local_path = materialized_dir(&dir_parent)?.join(file_name);
+        let reusable = std::fs::symlink_metadata(&local_path)
+            .is_ok_and(|metadata| metadata.is_file())
+            && file_sha256(&local_path).is_ok_and(|existing| existing == expected_sha256);
+        Ok((local_path, reusable))
+    })
+    .await
+    .map_err(|e| AppError::Custom(format!("Task join error: {}", e)))??;
+    if reusable {
+        return Ok(Some(local_path));
+    }
+
+    let stored: bool =
+        sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM source_blob WHERE sha256 = $1)"#)
+            .bind(sha256)
+            .fetch_one(pool)
+            .await?;
+    if !stored {
+        return Ok(None);
+    }
+
+    // Write under a temporary name first so readers never see a partial
+    // file. The name is created anew so an existing entry is not followed;
+    // the rename replaces whatever sits at the final name.
+    let partial = local_path.with_extension(format!("partial{}", std::process::id()));
+    let _ = tokio::fs::remove_file(&partial).await;
+    let mut options = tokio::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    options.mode(0o600);
+    let mut file = options.open(&partial).await?;
+
+    let mut hasher = Sha256::new();
+    for seq in 0i32.. {
+        let chunk: Option<Vec<u8>> = sqlx::query_scalar(
+            r#"SELECT data FROM source_blob_chunk WHERE sha256 = $1 AND seq = $2"#,
+        )
+        .bind(sha256)
+        .bind(seq)
+        .fetch_optional(pool)
+        .await?;
+        let Some(chunk) = chunk else {
+            break;
+        };
+        hasher.update(&chunk);
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    drop(file);
+
+    if format!("{:x}", hasher.finalize()) != sha256 {
+        let _ = tokio::fs::remove_file(&partial).await;
+        return Err(AppError::Custom(format!(
+            "The stored copy of {} is incomplete or damaged",
+            original_path.display()
+        )));
+    }
+    tokio::fs::rename(&partial, &local_path).await?;
+
+    Ok(Some(local_path))
+}
+
+/// Local path to open a source file from: `path` itself when it exists,
+/// otherwise a copy of the stored source with hash `sha256` under
+/// `cache_dir`. Falls back to `path` when neither is available.
+pub async fn resolve_source(
+    pool: &PgPool,
+    cache_dir: &Path,
+    path: &str,
+    sha256: Option<&str>,
+) -> Result<String> {
+    let original = Path::new(path);
+    if original.exists() {
+        return Ok(path.to_string());
+    }
+
+    if let Some(sha256) = sha256 {
+        if let Some(local_path) = materialize_source(pool, cache_dir, sha256, original).await? {
+            return Ok(local_path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(path.to_string())
+}
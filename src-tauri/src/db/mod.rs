@@ -1,5 +1,9 @@
 mod connection;
+mod content_store;
 mod models;
+mod schema;
 
 pub use connection::*;
+pub use content_store::*;
 pub use models::*;
+pub use schema::*;
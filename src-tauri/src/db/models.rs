@@ -41,6 +41,26 @@ pub struct PageInfo {
     pub document_id: i64,
     pub mimetype: Option<String>,
     pub page_metadata: Option<serde_json::Value>,
+    #[sqlx(json(nullable))]
+    pub image: Option<ImageInfo>, // page_metadata->'image'
+    /// Logical page label such as "iv" or "A-3", page_metadata->>'page_label'
+    pub page_label: Option<String>,
+}
+
+/// Properties of a stored page or chunk image, recorded at ingest in
+/// `page_metadata.image` and `chunk_metadata.image`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    /// Effective resolution; None for image files that do not declare one
+    pub dpi: Option<u32>,
+    pub byte_size: usize,
+    /// Hex SHA-256 of the stored bytes
+    pub sha256: String,
+    /// What produced the stored bytes, e.g. "pdftoppm 24.02.0";
+    /// None for image files stored as provided
+    pub renderer: Option<String>,
 }
 
 /// ImageChunk table - cropped image regions from pages
@@ -52,6 +72,7 @@ pub struct ImageChunk {
     #[serde(skip_serializing)]
     pub contents: Vec<u8>, // bytea NOT NULL (cropped image) - skip in JSON
     pub mimetype: String,         // varchar(255) NOT NULL
+    pub chunk_metadata: Option<serde_json::Value>, // jsonb - {"kind": "page" | "region", "bbox": ...}
 }
 
 /// ImageChunk without binary contents for list responses
@@ -60,6 +81,18 @@ pub struct ImageChunkInfo {
     pub id: i64,
     pub parent_page: Option<i64>,
     pub mimetype: String,
+    pub chunk_metadata: Option<serde_json::Value>,
+    #[sqlx(json(nullable))]
+    pub image: Option<ImageInfo>, // chunk_metadata->'image'
+}
+
+/// Chunk table - text chunks linked to pages through page_chunk_relation
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Chunk {
+    pub id: i64,                    // bigserial
+    pub contents: String,           // text NOT NULL
+    pub is_table: Option<bool>,     // boolean DEFAULT FALSE
+    pub table_type: Option<String>, // varchar(255)
 }
 
 /// Query table - user questions for RAG benchmarks
@@ -185,3 +218,61 @@ pub struct DocumentDeletionCheck {
     pub deletable: bool,
     pub blocking_queries: Vec<Query>,
 }
+
+/// A page with a perceptual hash in `page_metadata.fingerprint`
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FingerprintedPage {
+    pub page_id: i64,
+    pub document_id: i64,
+    pub document_title: Option<String>,
+    pub page_num: i32,
+    pub dhash: String,
+}
+
+/// Two pages whose perceptual hashes differ in at most a few bits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NearDuplicatePages {
+    pub first: FingerprintedPage,
+    pub second: FingerprintedPage,
+    /// Number of differing hash bits; 0 for visually identical pages
+    pub distance: u32,
+}
+
+/// Outcome of copying document sources into the content store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceStoreReport {
+    /// Sources copied into the store
+    pub stored: i32,
+    /// Sources whose content the store already held
+    pub already_stored: i32,
+    /// Source paths not found on this machine
+    pub missing: Vec<String>,
+    /// Source paths whose content no longer matches the hash recorded at import
+    pub changed: Vec<String>,
+    /// Source paths of files too large for the content store
+    pub too_large: Vec<String>,
+}
+
+/// Number of source paths changed by `relink_sources`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelinkReport {
+    /// `file` rows of PDF and office documents
+    pub files: i64,
+    /// Pages whose `page_metadata.source_path` changed, including PDF pages
+    pub pages: i64,
+    /// Source paths left unchanged because the new path would not fit in `file.path`
+    pub too_long: Vec<String>,
+}
+
+/// A document source that `verify_sources` found missing, unreadable or changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceProblem {
+    pub document_id: i64,
+    pub title: Option<String>,
+    pub path: String,
+    pub status: String, // "missing", "unreadable" or "changed"
+    /// Why the file could not be read, for "unreadable"
+    pub error: Option<String>,
+    /// Whether the content store holds the content recorded at import
+    pub in_content_store: bool,
+}
@@ -0,0 +1,38 @@
+use sqlx::PgPool;
+
+use crate::error::Result;
+
+/// Schema additions the app relies on beyond the original design in
+/// `postgresql/db/init/001-schema.sql`. They are applied on connect so that
+/// databases created before an addition keep working, which means every
+/// statement must be idempotent.
+const SCHEMA_UPGRADES: &[&str] = &[
+    "ALTER TABLE image_chunk ADD COLUMN IF NOT EXISTS chunk_metadata JSONB",
+    "CREATE INDEX IF NOT EXISTS idx_document_source_sha256 ON document ((doc_metadata->>'source_sha256'))",
+    r#"CREATE TABLE IF NOT EXISTS image_chunk_thumbnail (
+        image_chunk_id BIGINT NOT NULL REFERENCES image_chunk(id),
+        size INT NOT NULL,
+        contents BYTEA NOT NULL,
+        mimetype VARCHAR(255) NOT NULL,
+        PRIMARY KEY (image_chunk_id, size)
+    )"#,
+    r#"CREATE TABLE IF NOT EXISTS source_blob (
+        sha256 VARCHAR(64) PRIMARY KEY,
+        byte_size BIGINT NOT NULL
+    )"#,
+    r#"CREATE TABLE IF NOT EXISTS source_blob_chunk (
+        sha256 VARCHAR(64) NOT NULL REFERENCES source_blob(sha256),
+        seq INT NOT NULL,
+        data BYTEA NOT NULL,
+        PRIMARY KEY (sha256, seq)
+    )"#,
+];
+
+/// Apply `SCHEMA_UPGRADES` to the connected database
+pub async fn ensure_schema(pool: &PgPool) -> Result<()> {
+    for statement in SCHEMA_UPGRADES {
+        sqlx::query(statement).execute(pool).await?;
+    }
+
+    Ok(())
+}
@@ -17,6 +17,15 @@ pub enum AppError {
     #[error("PDF processing error: {0}")]
     PdfError(String),
 
+    #[error("PDF password required: the file is encrypted")]
+    PdfPasswordRequired,
+
+    #[error("PDF password incorrect")]
+    PdfWrongPassword,
+
+    #[error("PDF file is damaged or not a PDF: {0}")]
+    PdfCorrupt(String),
+
     #[error("Image processing error: {0}")]
     ImageError(String),
 
@@ -26,16 +35,58 @@ pub enum AppError {
     #[error("ZIP error: {0}")]
     Zip(#[from] zip::result::ZipError),
 
+    #[error("Ingestion job {0} was cancelled")]
+    Cancelled(u64),
+
+    #[error("Duplicate document: this source was already ingested as document {0}")]
+    DuplicateDocument(i64),
+
+    #[error("Source file changed since it was imported: {0}")]
+    SourceChanged(String),
+
+    #[error("Source file is too large for the content store: {0}")]
+    SourceTooLarge(String),
+
     #[error("{0}")]
     Custom(String),
 }
 
+impl AppError {
+    /// Stable name of the variant, for the frontend to match on
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => "database",
+            AppError::Io(_) => "io",
+            AppError::NotConnected => "not_connected",
+            AppError::NotFound(_) => "not_found",
+            AppError::PdfError(_) => "pdf_error",
+            AppError::PdfPasswordRequired => "pdf_password_required",
+            AppError::PdfWrongPassword => "pdf_wrong_password",
+            AppError::PdfCorrupt(_) => "pdf_corrupt",
+            AppError::ImageError(_) => "image_error",
+            AppError::Csv(_) => "csv",
+            AppError::Zip(_) => "zip",
+            AppError::Cancelled(_) => "cancelled",
+            AppError::DuplicateDocument(_) => "duplicate_document",
+            AppError::SourceChanged(_) => "source_changed",
+            AppError::SourceTooLarge(_) => "source_too_large",
+            AppError::Custom(_) => "custom",
+        }
+    }
+}
+
+/// Serialized as `{ "kind": ..., "message": ... }`
 impl serde::Serialize for AppError {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: serde::ser::Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("AppError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
     }
 }
 
@@ -0,0 +1,185 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+/// How page text is split into `chunk` rows.
+///
+/// Token counts are whitespace-separated words, which keeps chunking
+/// independent of any particular tokenizer model. Chunks never span pages.
+/// The chosen strategy is recorded in `doc_metadata.chunking`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "strategy", rename_all = "snake_case")]
+pub enum ChunkingStrategy {
+    /// One chunk per page
+    #[default]
+    Page,
+    /// Windows of `size` tokens, each sharing `overlap` tokens with the previous one
+    FixedTokens { size: usize, overlap: usize },
+    /// Whole sentences packed into chunks of at most `max_tokens` tokens
+    Sentence { max_tokens: usize },
+    /// Whole paragraphs (separated by blank lines) packed into chunks of at most `max_tokens` tokens
+    Paragraph { max_tokens: usize },
+}
+
+impl ChunkingStrategy {
+    /// Reject parameters that cannot produce chunks
+    pub fn validate(&self) -> Result<()> {
+        match *self {
+            ChunkingStrategy::Page => Ok(()),
+            ChunkingStrategy::FixedTokens { size, overlap } => {
+                if size == 0 {
+                    Err(AppError::Custom(
+                        "Chunk size must be greater than 0".to_string(),
+                    ))
+                } else if overlap >= size {
+                    Err(AppError::Custom(format!(
+                        "Chunk overlap ({}) must be smaller than chunk size ({})",
+                        overlap, size
+                    )))
+                } else {
+                    Ok(())
+                }
+            }
+            ChunkingStrategy::Sentence { max_tokens }
+            | ChunkingStrategy::Paragraph { max_tokens } => {
+                if max_tokens == 0 {
+                    Err(AppError::Custom(
+                        "Maximum chunk tokens must be greater than 0".to_string(),
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+
+    /// Split one page of text into chunk contents. Empty text yields no chunks.
+    pub fn split(&self, text: &str) -> Vec<String> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        match *self {
+            ChunkingStrategy::Page => vec![text.to_string()],
+            ChunkingStrategy::FixedTokens { size, overlap } => {
+                let tokens: Vec<&str> = text.split_whitespace().collect();
+                token_windows(&tokens, size, overlap)
+            }
+            ChunkingStrategy::Sentence { max_tokens } => {
+                pack_units(split_sentences(text), max_tokens, " ")
+            }
+            ChunkingStrategy::Paragraph { max_tokens } => {
+                pack_units(split_paragraphs(text), max_tokens, "\n\n")
+            }
+        }
+    }
+}
+
+/// Slide a window of `size` tokens over `tokens`, advancing by `size - overlap`
+fn token_windows(tokens: &[&str], size: usize, overlap: usize) -> Vec<String> {
+    let size = size.max(1);
+    let step = size.saturating_sub(overlap).max(1);
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < tokens.len() {
+        let end = (start + size).min(tokens.len());
+        chunks.push(tokens[start..end].join(" "));
+        if end == tokens.len() {
+            break;
+        }
+        start += step;
+    }
+    chunks
+}
+
+/// Greedily join consecutive units while the chunk stays within `max_tokens`.
+/// A single unit longer than `max_tokens` is cut into fixed windows.
+fn pack_units(units: Vec<String>, max_tokens: usize, separator: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current: Vec<String> = Vec::new();
+    let mut current_tokens = 0;
+
+    for unit in units {
+        let unit_tokens = unit.split_whitespace().count();
+
+        if unit_tokens > max_tokens {
+            if !current.is_empty() {
+                chunks.push(current.join(separator));
+                current.clear();
+                current_tokens = 0;
+            }
+            let tokens: Vec<&str> = unit.split_whitespace().collect();
+            chunks.extend(token_windows(&tokens, max_tokens, 0));
+            continue;
+        }
+
+        if current_tokens + unit_tokens > max_tokens && !current.is_empty() {
+            chunks.push(current.join(separator));
+            current.clear();
+            current_tokens = 0;
+        }
+
+        current_tokens += unit_tokens;
+        current.push(unit);
+    }
+
+    if !current.is_empty() {
+        chunks.push(current.join(separator));
+    }
+    chunks
+}
+
+/// Split text into sentences ending in `.`, `!`, `?` (or their CJK forms)
+/// followed by whitespace. Line breaks inside a sentence are collapsed.
+fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        current.push(c);
+
+        let is_terminal = matches!(c, '.' | '!' | '?' | '。' | '！' | '？');
+        let at_boundary = match chars.peek() {
+            Some(next) => next.is_whitespace() || matches!(c, '。' | '！' | '？'),
+            None => true,
+        };
+
+        if is_terminal && at_boundary {
+            push_normalized(&mut sentences, &current);
+            current.clear();
+        }
+    }
+    push_normalized(&mut sentences, &current);
+
+    sentences
+}
+
+/// Split text into paragraphs separated by one or more blank lines
+fn split_paragraphs(text: &str) -> Vec<String> {
+    let mut paragraphs = Vec::new();
+    let mut current = String::new();
+
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            push_normalized(&mut paragraphs, &current);
+            current.clear();
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    push_normalized(&mut paragraphs, &current);
+
+    paragraphs
+}
+
+/// Collapse runs of whitespace and keep the unit if anything is left
+fn push_normalized(units: &mut Vec<String>, raw: &str) {
+    let normalized = raw.split_whitespace().collect::<Vec<_>>().join(" ");
+    if !normalized.is_empty() {
+        units.push(normalized);
+    }
+}
@@ -0,0 +1,274 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+
+use super::multipage::is_image_extension;
+use super::office::OFFICE_EXTENSIONS;
+use super::types::IngestionResult;
+
+/// How `ingest_directory` selects files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DirectoryScanOptions {
+    /// Descend into subdirectories (symlinked directories are not followed)
+    pub recursive: bool,
+    /// Globs matched against paths relative to the directory, e.g. `reports/**`.
+    /// `*` also matches `/`. Empty includes every supported file.
+    pub include: Vec<String>,
+    /// Globs for files to leave out, applied after `include`
+    pub exclude: Vec<String>,
+    /// Ingest every image as its own document instead of one document per directory
+    pub image_per_document: bool,
+}
+
+/// One unit of work found by `scan_directory`
+#[derive(Debug, Clone)]
+pub enum IngestTarget {
+    Pdf(PathBuf),
+    /// Office document, converted to PDF during ingestion
+    Office(PathBuf),
+    /// Images ingested as one document, in file name order
+    Images {
+        title: String,
+        files: Vec<PathBuf>,
+    },
+}
+
+impl IngestTarget {
+    pub fn kind(&self) -> BatchItemKind {
+        match self {
+            IngestTarget::Pdf(_) => BatchItemKind::Pdf,
+            IngestTarget::Office(_) => BatchItemKind::Office,
+            IngestTarget::Images { .. } => BatchItemKind::Images,
+        }
+    }
+
+    pub fn files(&self) -> Vec<String> {
+        match self {
+            IngestTarget::Pdf(path) | IngestTarget::Office(path) => {
+                vec![path.to_string_lossy().into_owned()]
+            }
+            IngestTarget::Images { files, .. } => files
+                .iter()
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect(),
+        }
+    }
+}
+
+/// Collect the PDFs, office documents and image groups under `root`, in path order
+pub fn scan_directory(root: &Path, options: &DirectoryScanOptions) -> Result<Vec<IngestTarget>> {
+    if !root.is_dir() {
+        return Err(AppError::NotFound(format!(
+            "Directory not found: {}",
+            root.display()
+        )));
+    }
+
+    let scanner = Scanner {
+        root,
+        options,
+        include: build_globset(&options.include)?,
+        exclude: build_globset(&options.exclude)?,
+    };
+
+    let mut targets = Vec::new();
+    scanner.scan(root, &mut targets)?;
+    Ok(targets)
+}
+
+struct Scanner<'a> {
+    root: &'a Path,
+    options: &'a DirectoryScanOptions,
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl Scanner<'_> {
+    fn scan(&self, dir: &Path, targets: &mut Vec<IngestTarget>) -> Result<()> {
+        let mut entries = fs::read_dir(dir)?.collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        let mut images = Vec::new();
+        let mut subdirs = Vec::new();
+
+        for entry in entries {
+            let path = entry.path();
+            let file_type = entry.file_type()?;
+
+            if file_type.is_dir() {
+                if self.options.recursive {
+                    subdirs.push(path);
+                }
+                continue;
+            }
+            if !path.is_file() || !self.is_selected(&path) {
+                continue;
+            }
+
+            let extension = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| e.to_lowercase());
+            match extension.as_deref() {
+                Some("pdf") => targets.push(IngestTarget::Pdf(path)),
+                Some(ext) if OFFICE_EXTENSIONS.contains(&ext) => {
+                    targets.push(IngestTarget::Office(path))
+                }
+                Some(ext) if is_image_extension(ext) => images.push(path),
+                _ => {}
+            }
+        }
+
+        if self.options.image_per_document {
+            for path in images {
+                let title = path
+                    .file_stem()
+                    .map(|s| s.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                targets.push(IngestTarget::Images {
+                    title,
+                    files: vec![path],
+                });
+            }
+        } else if !images.is_empty() {
+            targets.push(IngestTarget::Images {
+                title: self.group_title(dir),
+                files: images,
+            });
+        }
+
+        for subdir in subdirs {
+            self.scan(&subdir, targets)?;
+        }
+        Ok(())
+    }
+
+    fn is_selected(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(self.root).unwrap_or(path);
+        let included = self
+            .include
+            .as_ref()
+            .is_none_or(|set| set.is_match(relative));
+        let excluded = self
+            .exclude
+            .as_ref()
+            .is_some_and(|set| set.is_match(relative));
+        included && !excluded
+    }
+
+    /// Directory path relative to the scanned root, or the root's own name
+    fn group_title(&self, dir: &Path) -> String {
+        let relative = dir.strip_prefix(self.root).unwrap_or(dir);
+        let title = if relative.as_os_str().is_empty() {
+            self.root
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+        } else {
+            Some(relative.to_string_lossy().into_owned())
+        };
+        title.unwrap_or_else(|| "Images".to_string())
+    }
+}
+
+fn build_globset(patterns: &[String]) -> Result<Option<GlobSet>> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = Glob::new(pattern)
+            .map_err(|e| AppError::Custom(format!("Invalid glob pattern '{}': {}", pattern, e)))?;
+        builder.add(glob);
+    }
+    let set = builder
+        .build()
+        .map_err(|e| AppError::Custom(format!("Invalid glob patterns: {}", e)))?;
+    Ok(Some(set))
+}
+
+/// What kind of item a directory ingestion found, see `IngestTarget`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchItemKind {
+    Pdf,
+    Office,
+    Images,
+}
+
+/// Outcome of one PDF or image group in a directory ingestion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchItemStatus {
+    Succeeded,
+    /// Already ingested before (duplicate source hash)
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItemReport {
+    pub kind: BatchItemKind,
+    pub files: Vec<String>,
+    pub status: BatchItemStatus,
+    pub result: Option<IngestionResult>,
+    pub message: Option<String>,
+}
+
+/// Per-item report returned by `ingest_directory`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirectoryIngestionReport {
+    pub directory: String,
+    pub items: Vec<BatchItemReport>,
+    pub succeeded: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    /// The batch was cancelled; items after the last reported one were not attempted
+    pub cancelled: bool,
+}
+
+impl DirectoryIngestionReport {
+    pub fn new(directory: String) -> Self {
+        Self {
+            directory,
+            items: Vec::new(),
+            succeeded: 0,
+            skipped: 0,
+            failed: 0,
+            cancelled: false,
+        }
+    }
+
+    /// Record the outcome of ingesting `target`
+    pub fn record(&mut self, target: &IngestTarget, outcome: Result<IngestionResult>) {
+        let (status, result, message) = match outcome {
+            Ok(result) if result.duplicate_of == Some(result.document_id) => {
+                let message = format!("Already ingested as document {}", result.document_id);
+                (BatchItemStatus::Skipped, Some(result), Some(message))
+            }
+            Ok(result) => (BatchItemStatus::Succeeded, Some(result), None),
+            Err(e @ AppError::DuplicateDocument(_)) => {
+                (BatchItemStatus::Skipped, None, Some(e.to_string()))
+            }
+            Err(e) => (BatchItemStatus::Failed, None, Some(e.to_string())),
+        };
+
+        match status {
+            BatchItemStatus::Succeeded => self.succeeded += 1,
+            BatchItemStatus::Skipped => self.skipped += 1,
+            BatchItemStatus::Failed => self.failed += 1,
+        }
+        self.items.push(BatchItemReport {
+            kind: target.kind(),
+            files: target.files(),
+            status,
+            result,
+            message,
+        });
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::{BinaryHeap, HashMap};
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+/// Pages are measured at this longest side for ink coverage
+const ANALYSIS_SIZE: u32 = 500;
+/// Luma below this value counts as ink
+const INK_THRESHOLD: u8 = 200;
+
+/// Perceptual fingerprint of a page image, stored as `page_metadata.fingerprint`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageFingerprint {
+    /// 64-bit difference hash as 16 hex digits; similar pages differ in few bits
+    pub dhash: String,
+    /// Fraction of the page covered by ink
+    pub ink_coverage: f64,
+    /// Whether the ink coverage is below the blank page threshold
+    pub blank: bool,
+}
+
+/// Fingerprint a page image. Pages with an ink coverage below
+/// `blank_threshold` are marked blank.
+pub fn fingerprint_page(img: &DynamicImage, blank_threshold: f64) -> PageFingerprint {
+    let ink_coverage = ink_coverage(img);
+
+    PageFingerprint {
+        dhash: format!("{:016x}", dhash(img)),
+        ink_coverage,
+        blank: ink_coverage < blank_threshold,
+    }
+}
+
+/// Difference hash: each bit tells whether a pixel of a 9x8 grayscale
+/// thumbnail is brighter than its right neighbour
+fn dhash(img: &DynamicImage) -> u64 {
+    let small = img.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+
+    let mut hash = 0u64;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = small.get_pixel(x, y).0[0];
+            let right = small.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | u64::from(left > right);
+        }
+    }
+    hash
+}
+
+fn ink_coverage(img: &DynamicImage) -> f64 {
+    let gray = if img.width().max(img.height()) > ANALYSIS_SIZE {
+        img.resize(ANALYSIS_SIZE, ANALYSIS_SIZE, FilterType::Triangle)
+            .to_luma8()
+    } else {
+        img.to_luma8()
+    };
+
+    let total = gray.pixels().len();
+    if total == 0 {
+        return 0.0;
+    }
+    let ink = gray.pixels().filter(|p| p.0[0] < INK_THRESHOLD).count();
+    ink as f64 / total as f64
+}
+
+/// Parse a stored `dhash` back into its 64 bits
+pub fn parse_dhash(dhash: &str) -> Option<u64> {
+    u64::from_str_radix(dhash, 16).ok()
+}
+
+/// Find the `limit` closest pairs of hashes at most `max_distance` bits apart.
+///
+/// Splits the 64 bits into `max_distance + 1` bands: by the pigeonhole
+/// principle two hashes within the distance agree on at least one band, so
+/// only hashes sharing a band value are compared. Returns index pairs
+/// `(i, j)` with `i < j` and their Hamming distance, closest first.
+pub fn near_duplicate_pairs(
+    hashes: &[u64],
+    max_distance: u32,
+    limit: usize,
+) -> Vec<(usize, usize, u32)> {
+    let bands = (max_distance + 1).min(64) as usize;
+    let band_mask = |band: usize| {
+        let start = band * 64 / bands;
+        let end = (band + 1) * 64 / bands;
+        if end - start == 64 {
+            u64::MAX
+        } else {
+            ((1u64 << (end - start)) - 1) << start
+        }
+    };
+
+    // Max-heap of the closest pairs so far, so memory stays within `limit`
+    let mut closest = BinaryHeap::new();
+
+    for band in 0..bands {
+        let mask = band_mask(band);
+        let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+        for (i, hash) in hashes.iter().enumerate() {
+            buckets.entry(hash & mask).or_default().push(i);
+        }
+
+        for bucket in buckets.values().filter(|bucket| bucket.len() > 1) {
+            for (k, &i) in bucket.iter().enumerate() {
+                for &j in &bucket[k + 1..] {
+                    let difference = hashes[i] ^ hashes[j];
+                    let distance = difference.count_ones();
+                    // A pair sharing several bands is counted in the first of them
+                    if distance > max_distance
+                        || (0..band).any(|earlier| difference & band_mask(earlier) == 0)
+                    {
+                        continue;
+                    }
+                    closest.push((distance, i, j));
+                    if closest.len() > limit {
+                        closest.pop();
+                    }
+                }
+            }
+        }
+    }
+
+    closest
+        .into_sorted_vec()
+        .into_iter()
+        .map(|(distance, i, j)| (i, j, distance))
+        .collect()
+}
@@ -0,0 +1,30 @@
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+use crate::error::Result;
+
+/// Hex-encoded SHA-256 of a byte slice
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Hex-encoded SHA-256 of a file, streamed so large sources are not loaded into memory
+pub fn file_sha256(path: &Path) -> Result<String> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader, &mut hasher)?;
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Hash identifying an ordered list of source files.
+/// A single source keeps its own hash so it matches the same file ingested alone.
+pub fn combined_sha256(hashes: &[String]) -> String {
+    match hashes {
+        [single] => single.clone(),
+        _ => sha256_hex(hashes.join("\n").as_bytes()),
+    }
+}
@@ -0,0 +1,114 @@
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::{ColorType, DynamicImage, ImageFormat};
+
+use crate::db::ImageInfo;
+use crate::error::{AppError, Result};
+
+use super::hash::sha256_hex;
+
+/// Formats browsers display natively, so their bytes can be stored unchanged
+const BROWSER_FORMATS: &[ImageFormat] = &[
+    ImageFormat::Png,
+    ImageFormat::Jpeg,
+    ImageFormat::Gif,
+    ImageFormat::WebP,
+];
+
+/// Renderer recorded for image files this app decoded and re-encoded
+pub const IMAGE_RENDERER: &str = concat!("AutoRAG Data ", env!("CARGO_PKG_VERSION"));
+
+/// JPEG quality used when re-encoding derived images (crops, thumbnails)
+const JPEG_QUALITY: u8 = 90;
+
+/// A downscaled JPEG copy of a chunk image for fast previews
+pub struct Thumbnail {
+    /// Bounding square the thumbnail fits in, in pixels
+    pub size: u32,
+    pub bytes: Vec<u8>,
+    pub mimetype: &'static str,
+}
+
+/// Decode image bytes in any enabled format
+pub fn decode_image(bytes: &[u8]) -> Result<DynamicImage> {
+    image::load_from_memory(bytes)
+        .map_err(|e| AppError::ImageError(format!("Failed to decode image: {}", e)))
+}
+
+/// Describe stored image bytes of the given dimensions
+pub fn image_info(
+    bytes: &[u8],
+    (width, height): (u32, u32),
+    dpi: Option<u32>,
+    renderer: Option<&str>,
+) -> ImageInfo {
+    ImageInfo {
+        width,
+        height,
+        dpi,
+        byte_size: bytes.len(),
+        sha256: sha256_hex(bytes),
+        renderer: renderer.map(str::to_string),
+    }
+}
+
+/// Mimetype of encoded image bytes if browsers can display them as they are
+pub fn browser_mimetype(bytes: &[u8]) -> Option<&'static str> {
+    image::guess_format(bytes)
+        .ok()
+        .filter(|format| BROWSER_FORMATS.contains(format))
+        .map(|format| format.to_mime_type())
+}
+
+/// File extension for a stored image mimetype, `bin` if it is not an image format
+pub fn extension_for_mimetype(mimetype: &str) -> &'static str {
+    ImageFormat::from_mime_type(mimetype)
+        .and_then(|format| format.extensions_str().first().copied())
+        .unwrap_or("bin")
+}
+
+/// Encode an image as JPEG when `mimetype` is `image/jpeg`, and as PNG otherwise.
+/// Returns the bytes together with the mimetype actually used.
+pub fn encode_image(img: &DynamicImage, mimetype: &str) -> Result<(Vec<u8>, &'static str)> {
+    let mut bytes = Vec::new();
+
+    if mimetype == "image/jpeg" {
+        let mut encoder = JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY);
+        // JPEG has no alpha channel; keep grayscale images single-channel
+        let result = match img.color() {
+            ColorType::L8 => encoder.encode_image(&img.to_luma8()),
+            _ => encoder.encode_image(&img.to_rgb8()),
+        };
+        result.map_err(|e| AppError::ImageError(format!("Failed to encode as JPEG: {}", e)))?;
+        return Ok((bytes, "image/jpeg"));
+    }
+
+    img.write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+        .map_err(|e| AppError::ImageError(format!("Failed to encode as PNG: {}", e)))?;
+    Ok((bytes, "image/png"))
+}
+
+/// Make one thumbnail per distinct non-zero size, each fitting in a
+/// `size`x`size` square. Images already small enough are not upscaled.
+pub fn make_thumbnails(img: &DynamicImage, sizes: &[u32]) -> Result<Vec<Thumbnail>> {
+    let mut sizes: Vec<u32> = sizes.iter().copied().filter(|&s| s > 0).collect();
+    sizes.sort_unstable();
+    sizes.dedup();
+
+    sizes
+        .into_iter()
+        .map(|size| {
+            let (bytes, mimetype) = if img.width().max(img.height()) > size {
+                encode_image(&img.thumbnail(size, size), "image/jpeg")?
+            } else {
+                encode_image(img, "image/jpeg")?
+            };
+            Ok(Thumbnail {
+                size,
+                bytes,
+                mimetype,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,307 @@
+use image::imageops::FilterType;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+
+use super::region::{crop_image, BoundingBox, CroppedRegion};
+
+/// Pages are analysed at this longest side; detection does not need full resolution
+const ANALYSIS_SIZE: u32 = 1000;
+/// Luma below this value counts as ink
+const INK_THRESHOLD: u8 = 200;
+/// A row/column with at most this fraction of ink pixels counts as whitespace
+const EMPTY_LINE_RATIO: f64 = 0.002;
+/// Minimum whitespace gap (fraction of page height) for a horizontal cut
+const MIN_ROW_GAP: f64 = 0.015;
+/// Minimum whitespace gap (fraction of page width) for a vertical cut
+const MIN_COLUMN_GAP: f64 = 0.02;
+/// Regions smaller than this fraction of the page are ignored
+const MIN_REGION_AREA: f64 = 0.01;
+/// Regions larger than this fraction of the page duplicate the full-page chunk
+const MAX_REGION_AREA: f64 = 0.9;
+/// Maximum recursion depth of the XY-cut
+const MAX_DEPTH: usize = 12;
+/// A row at least this fraction inked may be a table ruling line
+const RULING_LINE_RATIO: f64 = 0.9;
+/// Ruling lines are at most this many pixels thick at analysis size
+const MAX_RULING_THICKNESS: usize = 4;
+
+/// What a detected region most likely contains
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegionLabel {
+    Figure,
+    Table,
+    Text,
+}
+
+/// A candidate region found on a rendered page
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedRegion {
+    pub label: RegionLabel,
+    pub bbox: BoundingBox,
+    /// Fraction of the region covered by ink
+    pub ink_coverage: f64,
+}
+
+/// A detected region together with its cropped image, ready to be stored
+pub struct RegionCandidate {
+    pub region: DetectedRegion,
+    pub crop: CroppedRegion,
+}
+
+/// Detect regions on a page image and crop each of them, encoded as `mimetype`
+pub fn detect_region_candidates(
+    img: &DynamicImage,
+    mimetype: &str,
+) -> Result<Vec<RegionCandidate>> {
+    detect_regions(img)
+        .into_iter()
+        .map(|region| {
+            let crop = crop_image(img, mimetype, &region.bbox)?;
+            Ok(RegionCandidate { region, crop })
+        })
+        .collect()
+}
+
+/// Binarized page, one flag per pixel in row-major order
+struct InkMap {
+    width: u32,
+    height: u32,
+    ink: Vec<bool>,
+}
+
+impl InkMap {
+    fn new(img: &DynamicImage) -> Self {
+        let gray = downscale(img).to_luma8();
+        let (width, height) = gray.dimensions();
+        let ink = gray.pixels().map(|p| p.0[0] < INK_THRESHOLD).collect();
+
+        Self { width, height, ink }
+    }
+
+    fn is_ink(&self, x: u32, y: u32) -> bool {
+        self.ink[(y * self.width + x) as usize]
+    }
+}
+
+/// Pixel rectangle used while cutting, as half-open ranges
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    x0: u32,
+    y0: u32,
+    x1: u32,
+    y1: u32,
+}
+
+impl Block {
+    fn width(&self) -> u32 {
+        self.x1 - self.x0
+    }
+
+    fn height(&self) -> u32 {
+        self.y1 - self.y0
+    }
+}
+
+/// Detect figure, table and text blocks on a page image.
+///
+/// Uses a recursive XY-cut: the page is binarized, split along horizontal
+/// and vertical whitespace gaps found in the ink projection profiles, and
+/// each resulting block is classified from its ink density and ruling lines.
+/// This is a CPU-only heuristic meant to pre-populate candidates for review.
+pub fn detect_regions(img: &DynamicImage) -> Vec<DetectedRegion> {
+    let map = InkMap::new(img);
+    let (width, height) = (map.width, map.height);
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+
+    let mut blocks = Vec::new();
+    let page = Block {
+        x0: 0,
+        y0: 0,
+        x1: width,
+        y1: height,
+    };
+    if let Some(page) = trim_to_ink(&map, page) {
+        xy_cut(&map, page, 0, &mut blocks);
+    }
+
+    let page_area = (width * height) as f64;
+    blocks
+        .into_iter()
+        .filter_map(|block| {
+            let area = (block.width() * block.height()) as f64 / page_area;
+            if !(MIN_REGION_AREA..=MAX_REGION_AREA).contains(&area) {
+                return None;
+            }
+
+            let (label, ink_coverage) = classify(&map, block);
+            Some(DetectedRegion {
+                label,
+                bbox: BoundingBox {
+                    x: block.x0 as f64 / width as f64,
+                    y: block.y0 as f64 / height as f64,
+                    width: block.width() as f64 / width as f64,
+                    height: block.height() as f64 / height as f64,
+                },
+                ink_coverage,
+            })
+        })
+        .collect()
+}
+
+fn downscale(img: &DynamicImage) -> DynamicImage {
+    if img.width().max(img.height()) <= ANALYSIS_SIZE {
+        img.clone()
+    } else {
+        img.resize(ANALYSIS_SIZE, ANALYSIS_SIZE, FilterType::Triangle)
+    }
+}
+
+/// Ink pixel count of every row (horizontal = true) or column of a block
+fn projection(map: &InkMap, block: Block, horizontal: bool) -> Vec<u32> {
+    if horizontal {
+        (block.y0..block.y1)
+            .map(|y| (block.x0..block.x1).filter(|&x| map.is_ink(x, y)).count() as u32)
+            .collect()
+    } else {
+        (block.x0..block.x1)
+            .map(|x| (block.y0..block.y1).filter(|&y| map.is_ink(x, y)).count() as u32)
+            .collect()
+    }
+}
+
+/// Shrink a block to the bounding box of its ink, or None if it has no ink
+fn trim_to_ink(map: &InkMap, block: Block) -> Option<Block> {
+    let rows = projection(map, block, true);
+    let columns = projection(map, block, false);
+
+    let top = rows.iter().position(|&n| n > 0)? as u32;
+    let bottom = rows.iter().rposition(|&n| n > 0)? as u32 + 1;
+    let left = columns.iter().position(|&n| n > 0)? as u32;
+    let right = columns.iter().rposition(|&n| n > 0)? as u32 + 1;
+
+    Some(Block {
+        x0: block.x0 + left,
+        y0: block.y0 + top,
+        x1: block.x0 + right,
+        y1: block.y0 + bottom,
+    })
+}
+
+/// Split `profile` into segments separated by whitespace runs of at least `min_gap`
+fn split_profile(profile: &[u32], line_length: u32, min_gap: usize) -> Vec<(u32, u32)> {
+    let empty_limit = (line_length as f64 * EMPTY_LINE_RATIO) as u32;
+
+    let mut segments = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut gap = 0;
+
+    for (i, &count) in profile.iter().enumerate() {
+        if count > empty_limit {
+            if start.is_none() {
+                start = Some(i);
+            }
+            gap = 0;
+        } else if let Some(s) = start {
+            gap += 1;
+            if gap >= min_gap {
+                segments.push((s as u32, (i + 1 - gap) as u32));
+                start = None;
+                gap = 0;
+            }
+        }
+    }
+    if let Some(s) = start {
+        segments.push((s as u32, (profile.len() - gap) as u32));
+    }
+
+    segments
+}
+
+fn xy_cut(map: &InkMap, block: Block, depth: usize, out: &mut Vec<Block>) {
+    if depth >= MAX_DEPTH {
+        out.push(block);
+        return;
+    }
+
+    let min_row_gap = ((map.height as f64 * MIN_ROW_GAP) as usize).max(2);
+    let min_column_gap = ((map.width as f64 * MIN_COLUMN_GAP) as usize).max(2);
+
+    // Prefer horizontal cuts (reading order), fall back to vertical ones
+    let rows = split_profile(&projection(map, block, true), block.width(), min_row_gap);
+    let parts: Vec<Block> = if rows.len() > 1 {
+        rows.into_iter()
+            .map(|(y0, y1)| Block {
+                y0: block.y0 + y0,
+                y1: block.y0 + y1,
+                ..block
+            })
+            .collect()
+    } else {
+        let columns = split_profile(
+            &projection(map, block, false),
+            block.height(),
+            min_column_gap,
+        );
+        if columns.len() <= 1 {
+            out.push(block);
+            return;
+        }
+        columns
+            .into_iter()
+            .map(|(x0, x1)| Block {
+                x0: block.x0 + x0,
+                x1: block.x0 + x1,
+                ..block
+            })
+            .collect()
+    };
+
+    for part in parts {
+        if let Some(part) = trim_to_ink(map, part) {
+            xy_cut(map, part, depth + 1, out);
+        }
+    }
+}
+
+/// Classify a leaf block and return its ink coverage.
+///
+/// Tables have several thin rows inked across almost the full width (ruling
+/// lines); figures are dense or lack the blank gaps between lines of text.
+fn classify(map: &InkMap, block: Block) -> (RegionLabel, f64) {
+    let rows = projection(map, block, true);
+    let area = (block.width() * block.height()) as f64;
+    let ink: u32 = rows.iter().sum();
+    let coverage = ink as f64 / area;
+
+    let full_width = block.width() as f64 * RULING_LINE_RATIO;
+    let mut ruling_lines = 0;
+    let mut run = 0;
+    for &count in rows.iter().chain(std::iter::once(&0)) {
+        if count as f64 >= full_width {
+            run += 1;
+        } else {
+            if (1..=MAX_RULING_THICKNESS).contains(&run) {
+                ruling_lines += 1;
+            }
+            run = 0;
+        }
+    }
+    if ruling_lines >= 3 {
+        return (RegionLabel::Table, coverage);
+    }
+
+    // Running text leaves blank rows between its lines; a single line does not
+    let blank_rows = rows.iter().filter(|&&n| n == 0).count() as f64 / rows.len() as f64;
+    let label = if coverage > 0.6 || (blank_rows < 0.1 && block.height() > 30) {
+        RegionLabel::Figure
+    } else {
+        RegionLabel::Text
+    };
+
+    (label, coverage)
+}
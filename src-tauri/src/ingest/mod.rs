@@ -1,5 +1,38 @@
+mod chunking;
+mod directory;
+mod fingerprint;
+mod hash;
+mod imaging;
+mod layout;
+mod multipage;
+mod normalize;
+mod office;
+mod page_range;
 mod pdf;
+#[cfg(feature = "pdfium")]
+mod pdfium;
+#[cfg(not(feature = "pdfium"))]
+mod poppler;
+mod region;
+mod structure;
 mod types;
 
-pub use pdf::process_pdf;
-pub use types::{IngestionProgress, IngestionResult};
+pub use chunking::ChunkingStrategy;
+pub use directory::{scan_directory, DirectoryIngestionReport, DirectoryScanOptions, IngestTarget};
+pub use fingerprint::{fingerprint_page, near_duplicate_pairs, parse_dhash, PageFingerprint};
+pub use hash::{combined_sha256, file_sha256};
+pub use imaging::{
+    browser_mimetype, decode_image, encode_image, extension_for_mimetype, image_info,
+    make_thumbnails, Thumbnail, IMAGE_RENDERER,
+};
+pub use layout::RegionCandidate;
+pub use multipage::{image_extensions, load_image_frames};
+pub use normalize::SourceImageInfo;
+pub use office::{convert_to_pdf, office_extension, ConvertedPdf, OFFICE_EXTENSIONS};
+pub use page_range::parse_page_ranges;
+pub use pdf::{renderer, stream_pages};
+pub use region::{crop_image, crop_region, BoundingBox, CroppedRegion};
+pub use types::{
+    BlankPagePolicy, DuplicatePolicy, IngestOptions, IngestionProgress, IngestionResult,
+    RenderOptions, RerenderResult, SupportedFormats,
+};
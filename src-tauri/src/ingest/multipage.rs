@@ -0,0 +1,303 @@
+use std::io::{Cursor, Read, Seek};
+use std::path::Path;
+
+use image::codecs::jpeg::JpegDecoder;
+use image::codecs::png::PngDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::{DynamicImage, ImageBuffer, ImageDecoder, ImageFormat};
+use tiff::decoder::{Decoder, DecodingResult};
+use tiff::tags::Tag;
+use tiff::ColorType;
+
+use crate::error::{AppError, Result};
+
+use super::normalize::{
+    apply_orientation, cmyk_to_srgb, convert_to_srgb, read_exif, resolution_to_dpi, ColorModel,
+    SourceImageInfo,
+};
+
+/// TIFF tag holding an embedded ICC profile
+const TIFF_ICC_PROFILE_TAG: u16 = 34675;
+
+/// Extensions of image files that can be ingested, derived from the
+/// decoders enabled in the `image` crate
+pub fn image_extensions() -> Vec<&'static str> {
+    ImageFormat::all()
+        .filter(|format| format.reading_enabled())
+        .flat_map(|format| format.extensions_str().iter().copied())
+        .collect()
+}
+
+/// Whether files with this (lowercase) extension can be ingested as images
+pub fn is_image_extension(extension: &str) -> bool {
+    ImageFormat::from_extension(extension).is_some_and(|format| format.reading_enabled())
+}
+
+/// One page of an image file, upright and in sRGB
+pub struct ImageFrame {
+    pub image: DynamicImage,
+    pub source: SourceImageInfo,
+}
+
+/// A decoded page before orientation and color profile normalization
+struct RawFrame {
+    image: DynamicImage,
+    color_model: ColorModel,
+    icc_profile: Option<Vec<u8>>,
+    /// Set for CMYK pages, which are converted to sRGB while decoding
+    icc_converted: bool,
+    orientation: u32,
+    dpi: Option<u32>,
+}
+
+/// Decode an image file into one image per page. Multi-page TIFFs yield
+/// every page; other formats yield a single image (the first frame of an
+/// animated GIF or WebP). Pages are rotated upright according to their EXIF
+/// orientation and converted to sRGB using their embedded ICC profile.
+pub fn load_image_frames(path: &Path) -> Result<Vec<ImageFrame>> {
+    let bytes = std::fs::read(path)?;
+    let format = image::guess_format(&bytes)
+        .or_else(|_| ImageFormat::from_path(path))
+        .map_err(|e| AppError::ImageError(format!("Failed to read image: {}", e)))?;
+
+    if format == ImageFormat::Tiff {
+        let frames = decode_tiff_frames(&bytes)?;
+        return Ok(frames.into_iter().map(normalize_frame).collect());
+    }
+
+    let frame = match format {
+        ImageFormat::Jpeg => decode_jpeg(&bytes)?,
+        ImageFormat::Png => decode_with_profile(PngDecoder::new(Cursor::new(&bytes)))?,
+        ImageFormat::WebP => decode_with_profile(WebPDecoder::new(Cursor::new(&bytes)))?,
+        _ => {
+            let image =
+                image::load_from_memory_with_format(&bytes, format).map_err(decode_error)?;
+            RawFrame {
+                color_model: color_model(&image),
+                image,
+                icc_profile: None,
+                icc_converted: false,
+                orientation: 1,
+                dpi: None,
+            }
+        }
+    };
+
+    let exif = read_exif(&bytes);
+    Ok(vec![normalize_frame(RawFrame {
+        orientation: exif.orientation,
+        dpi: exif.dpi,
+        ..frame
+    })])
+}
+
+fn normalize_frame(raw: RawFrame) -> ImageFrame {
+    let mut image = raw.image;
+    let mut icc_converted = raw.icc_converted;
+
+    if raw.color_model == ColorModel::Rgb {
+        if let Some(converted) = raw
+            .icc_profile
+            .and_then(|icc| convert_to_srgb(&image, &icc))
+        {
+            image = converted;
+            icc_converted = true;
+        }
+    }
+
+    let source = SourceImageInfo {
+        width: image.width(),
+        height: image.height(),
+        orientation: raw.orientation,
+        dpi: raw.dpi,
+        color_model: raw.color_model,
+        icc_converted,
+    };
+
+    ImageFrame {
+        image: apply_orientation(image, raw.orientation),
+        source,
+    }
+}
+
+fn color_model(image: &DynamicImage) -> ColorModel {
+    if image.color().has_color() {
+        ColorModel::Rgb
+    } else {
+        ColorModel::Gray
+    }
+}
+
+/// Decode with one of the `image` crate's decoders, keeping its ICC profile
+fn decode_with_profile<'a, D: ImageDecoder<'a>>(
+    decoder: image::ImageResult<D>,
+) -> Result<RawFrame> {
+    let mut decoder = decoder.map_err(decode_error)?;
+    let icc_profile = decoder.icc_profile();
+    let image = DynamicImage::from_decoder(decoder).map_err(decode_error)?;
+
+    Ok(RawFrame {
+        color_model: color_model(&image),
+        image,
+        icc_profile,
+        icc_converted: false,
+        orientation: 1,
+        dpi: None,
+    })
+}
+
+/// Decode a JPEG. CMYK JPEGs are decoded to raw CMYK so that their ICC
+/// profile can be applied; the `image` crate converts them naively.
+fn decode_jpeg(bytes: &[u8]) -> Result<RawFrame> {
+    let mut decoder = jpeg_decoder::Decoder::new(Cursor::new(bytes));
+    decoder.read_info().map_err(jpeg_error)?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| AppError::ImageError("JPEG has no frame header".to_string()))?;
+
+    if info.pixel_format != jpeg_decoder::PixelFormat::CMYK32 {
+        return decode_with_profile(JpegDecoder::new(Cursor::new(bytes)));
+    }
+
+    let cmyk = decoder.decode().map_err(jpeg_error)?;
+    let (rgb, icc_converted) = cmyk_to_srgb(&cmyk, decoder.icc_profile().as_deref());
+    let image = ImageBuffer::from_raw(info.width as u32, info.height as u32, rgb)
+        .map(DynamicImage::ImageRgb8)
+        .ok_or_else(|| AppError::ImageError("JPEG data does not match its size".to_string()))?;
+
+    Ok(RawFrame {
+        image,
+        color_model: ColorModel::Cmyk,
+        icc_profile: None,
+        icc_converted,
+        orientation: 1,
+        dpi: None,
+    })
+}
+
+fn decode_tiff_frames(bytes: &[u8]) -> Result<Vec<RawFrame>> {
+    let mut decoder = Decoder::new(Cursor::new(bytes)).map_err(tiff_error)?;
+
+    let mut frames = Vec::new();
+    loop {
+        frames.push(decode_tiff_frame(&mut decoder)?);
+        if !decoder.more_images() {
+            break;
+        }
+        decoder.next_image().map_err(tiff_error)?;
+    }
+    Ok(frames)
+}
+
+/// Decode the decoder's current page
+fn decode_tiff_frame<R: Read + Seek>(decoder: &mut Decoder<R>) -> Result<RawFrame> {
+    let (width, height) = decoder.dimensions().map_err(tiff_error)?;
+    let color_type = decoder.colortype().map_err(tiff_error)?;
+    let icc_profile = decoder
+        .get_tag_u8_vec(Tag::Unknown(TIFF_ICC_PROFILE_TAG))
+        .ok();
+    let orientation = decoder
+        .find_tag_unsigned::<u32>(Tag::Orientation)
+        .ok()
+        .flatten()
+        .filter(|orientation| (1..=8).contains(orientation))
+        .unwrap_or(1);
+    let dpi = tiff_dpi(decoder);
+    let data = decoder.read_image().map_err(tiff_error)?;
+
+    // Whether the ICC profile was applied, for CMYK pages
+    let mut cmyk_icc_converted = None;
+    let img = match (color_type, data) {
+        (ColorType::Gray(8), DecodingResult::U8(buf)) => {
+            ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageLuma8)
+        }
+        (ColorType::GrayA(8), DecodingResult::U8(buf)) => {
+            ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageLumaA8)
+        }
+        (ColorType::RGB(8), DecodingResult::U8(buf)) => {
+            ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageRgb8)
+        }
+        (ColorType::RGBA(8), DecodingResult::U8(buf)) => {
+            ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageRgba8)
+        }
+        (ColorType::CMYK(8), DecodingResult::U8(buf)) => {
+            let (rgb, icc_converted) = cmyk_to_srgb(&buf, icc_profile.as_deref());
+            cmyk_icc_converted = Some(icc_converted);
+            ImageBuffer::from_raw(width, height, rgb).map(DynamicImage::ImageRgb8)
+        }
+        (ColorType::Gray(16), DecodingResult::U16(buf)) => {
+            ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageLuma16)
+        }
+        (ColorType::GrayA(16), DecodingResult::U16(buf)) => {
+            ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageLumaA16)
+        }
+        (ColorType::RGB(16), DecodingResult::U16(buf)) => {
+            ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageRgb16)
+        }
+        (ColorType::RGBA(16), DecodingResult::U16(buf)) => {
+            ImageBuffer::from_raw(width, height, buf).map(DynamicImage::ImageRgba16)
+        }
+        _ => {
+            return Err(AppError::ImageError(format!(
+                "Unsupported TIFF color type: {:?}",
+                color_type
+            )))
+        }
+    };
+
+    let image = img.ok_or_else(|| {
+        AppError::ImageError("TIFF page data does not match its size".to_string())
+    })?;
+
+    Ok(match cmyk_icc_converted {
+        Some(icc_converted) => RawFrame {
+            image,
+            color_model: ColorModel::Cmyk,
+            icc_profile: None,
+            icc_converted,
+            orientation,
+            dpi,
+        },
+        None => RawFrame {
+            color_model: color_model(&image),
+            image,
+            icc_profile,
+            icc_converted: false,
+            orientation,
+            dpi,
+        },
+    })
+}
+
+/// Resolution of the decoder's current page from its XResolution tag
+fn tiff_dpi<R: Read + Seek>(decoder: &mut Decoder<R>) -> Option<u32> {
+    let resolution = decoder
+        .find_tag(Tag::XResolution)
+        .ok()??
+        .into_u32_vec()
+        .ok()?;
+    let unit = decoder
+        .find_tag_unsigned::<u32>(Tag::ResolutionUnit)
+        .ok()
+        .flatten()
+        .unwrap_or(2);
+
+    match resolution[..] {
+        [numerator, denominator] if denominator > 0 => {
+            resolution_to_dpi(numerator as f64 / denominator as f64, unit)
+        }
+        _ => None,
+    }
+}
+
+fn tiff_error(e: tiff::TiffError) -> AppError {
+    AppError::ImageError(format!("Failed to decode TIFF: {}", e))
+}
+
+fn jpeg_error(e: jpeg_decoder::Error) -> AppError {
+    AppError::ImageError(format!("Failed to decode JPEG: {}", e))
+}
+
+fn decode_error(e: image::ImageError) -> AppError {
+    AppError::ImageError(format!("Failed to decode image: {}", e))
+}
@@ -0,0 +1,165 @@
+use std::io::Cursor;
+
+use image::DynamicImage;
+use qcms::{DataType, Intent, Profile, Transform};
+use serde::Serialize;
+
+/// Color model of the pixels as encoded in the source file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorModel {
+    Gray,
+    Rgb,
+    Cmyk,
+}
+
+/// What a source image looked like before it was normalized for storage.
+/// Recorded as `page_metadata.original`.
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceImageInfo {
+    /// Width as encoded in the file, before EXIF rotation
+    pub width: u32,
+    /// Height as encoded in the file, before EXIF rotation
+    pub height: u32,
+    /// EXIF orientation (1-8); 1 when the file has none
+    pub orientation: u32,
+    /// Resolution declared in EXIF or TIFF tags
+    pub dpi: Option<u32>,
+    pub color_model: ColorModel,
+    /// Whether an embedded ICC profile was used to convert the pixels to sRGB
+    pub icc_converted: bool,
+}
+
+impl SourceImageInfo {
+    /// Whether the normalized pixels differ from the encoded ones
+    pub fn is_normalized(&self) -> bool {
+        self.orientation != 1 || self.color_model == ColorModel::Cmyk || self.icc_converted
+    }
+}
+
+/// Orientation and resolution declared in an image's EXIF data
+pub struct ExifTags {
+    /// 1 (upright) when the image has none
+    pub orientation: u32,
+    pub dpi: Option<u32>,
+}
+
+/// Read the EXIF tags of an encoded image; missing or broken EXIF data
+/// yields the defaults
+pub fn read_exif(bytes: &[u8]) -> ExifTags {
+    let exif = exif::Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok();
+    let field = |tag| {
+        exif.as_ref()
+            .and_then(|exif| exif.get_field(tag, exif::In::PRIMARY))
+    };
+
+    let orientation = field(exif::Tag::Orientation)
+        .and_then(|field| field.value.get_uint(0))
+        .filter(|orientation| (1..=8).contains(orientation))
+        .unwrap_or(1);
+    let resolution = field(exif::Tag::XResolution).and_then(|field| match &field.value {
+        exif::Value::Rational(values) => values.first().map(|value| value.to_f64()),
+        _ => None,
+    });
+    let unit = field(exif::Tag::ResolutionUnit)
+        .and_then(|field| field.value.get_uint(0))
+        .unwrap_or(2);
+
+    ExifTags {
+        orientation,
+        dpi: resolution.and_then(|resolution| resolution_to_dpi(resolution, unit)),
+    }
+}
+
+/// Convert an EXIF/TIFF resolution to dots per inch. Unit 2 is inches,
+/// 3 centimeters; unit 1 (none) only gives an aspect ratio.
+pub fn resolution_to_dpi(resolution: f64, unit: u32) -> Option<u32> {
+    let dpi = match unit {
+        2 => resolution,
+        3 => resolution * 2.54,
+        _ => return None,
+    };
+    (dpi.is_finite() && dpi >= 1.0).then(|| dpi.round() as u32)
+}
+
+/// Rotate and flip an image so that it displays upright for an EXIF orientation
+pub fn apply_orientation(img: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => img.fliph(),
+        3 => img.rotate180(),
+        4 => img.flipv(),
+        5 => img.rotate90().fliph(),
+        6 => img.rotate90(),
+        7 => img.rotate270().fliph(),
+        8 => img.rotate270(),
+        _ => img,
+    }
+}
+
+/// Convert CMYK pixels (0 = no ink) to sRGB. Uses the embedded ICC profile
+/// when it is a usable CMYK profile, otherwise the naive conversion the
+/// `image` crate applies. Returns the RGB pixels and whether the profile was used.
+pub fn cmyk_to_srgb(cmyk: &[u8], icc_profile: Option<&[u8]>) -> (Vec<u8>, bool) {
+    let transform = icc_profile.and_then(|icc| {
+        let input = Profile::new_from_slice(icc, false)?;
+        Transform::new_to(
+            &input,
+            &srgb_profile(),
+            DataType::CMYK,
+            DataType::RGB8,
+            Intent::default(),
+        )
+    });
+
+    match transform {
+        Some(transform) => {
+            let mut rgb = vec![0; cmyk.len() / 4 * 3];
+            transform.convert(&cmyk[..cmyk.len() / 4 * 4], &mut rgb);
+            (rgb, true)
+        }
+        None => (naive_cmyk_to_rgb(cmyk), false),
+    }
+}
+
+/// Convert an RGB image tagged with a non-sRGB ICC profile to sRGB.
+/// Returns None when the profile is sRGB, unusable, or not an RGB profile.
+pub fn convert_to_srgb(img: &DynamicImage, icc_profile: &[u8]) -> Option<DynamicImage> {
+    let input = Profile::new_from_slice(icc_profile, false)?;
+    if input.is_sRGB() {
+        return None;
+    }
+
+    let output = srgb_profile();
+    if img.color().has_alpha() {
+        let transform = Transform::new(&input, &output, DataType::RGBA8, Intent::default())?;
+        let mut rgba = img.to_rgba8();
+        transform.apply(&mut rgba);
+        Some(DynamicImage::ImageRgba8(rgba))
+    } else {
+        let transform = Transform::new(&input, &output, DataType::RGB8, Intent::default())?;
+        let mut rgb = img.to_rgb8();
+        transform.apply(&mut rgb);
+        Some(DynamicImage::ImageRgb8(rgb))
+    }
+}
+
+fn srgb_profile() -> Box<Profile> {
+    let mut profile = Profile::new_sRGB();
+    profile.precache_output_transform();
+    profile
+}
+
+fn naive_cmyk_to_rgb(cmyk: &[u8]) -> Vec<u8> {
+    cmyk.chunks_exact(4)
+        .flat_map(|px| {
+            let k = 255 - px[3] as u16;
+            [
+                ((255 - px[0] as u16) * k / 255) as u8,
+                ((255 - px[1] as u16) * k / 255) as u8,
+                ((255 - px[2] as u16) * k / 255) as u8,
+            ]
+        })
+        .collect()
+}
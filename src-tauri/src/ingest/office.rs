@@ -0,0 +1,183 @@
+use std::fs::{self, File};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::{AppError, Result};
+
+/// Extensions of office documents that are converted to PDF before ingestion
+pub const OFFICE_EXTENSIONS: &[&str] = &[
+    "doc", "docx", "odt", "rtf", "ppt", "pptx", "odp", "xls", "xlsx", "ods",
+];
+
+/// LibreOffice executables tried in order
+const CONVERTERS: &[&str] = &["soffice", "libreoffice"];
+
+/// Longest a single conversion may take before LibreOffice is killed
+const CONVERT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// How often a running conversion is checked for completion or cancellation
+const CONVERT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Monotonic counter to ensure unique conversion directories across concurrent calls
+static CONVERT_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Lowercase extension of `path` if it is an office document
+pub fn office_extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .filter(|e| OFFICE_EXTENSIONS.contains(&e.as_str()))
+}
+
+/// PDF produced by `convert_to_pdf`. It lives in a temporary directory
+/// that is removed when this value is dropped.
+pub struct ConvertedPdf {
+    dir: PathBuf,
+    pdf: PathBuf,
+    /// Executable that did the conversion
+    pub converter: &'static str,
+}
+
+impl ConvertedPdf {
+    pub fn path(&self) -> &Path {
+        &self.pdf
+    }
+}
+
+impl Drop for ConvertedPdf {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Convert an office document to PDF with headless LibreOffice.
+/// Each conversion uses its own profile directory, so several can run at
+/// once without contending for LibreOffice's user profile lock.
+/// LibreOffice is killed when `is_cancelled` returns true or the conversion
+/// exceeds `CONVERT_TIMEOUT`; both fail the conversion.
+pub fn convert_to_pdf(path: &Path, is_cancelled: impl Fn() -> bool) -> Result<ConvertedPdf> {
+    let id = CONVERT_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("autorag_convert_{}_{}", std::process::id(), id));
+    fs::create_dir_all(&dir)?;
+
+    // LibreOffice names the output after the input, replacing only the last extension
+    let mut pdf_name = path
+        .file_stem()
+        .ok_or_else(|| AppError::PdfError(format!("Invalid file name: {}", path.display())))?
+        .to_os_string();
+    pdf_name.push(".pdf");
+    let mut converted = ConvertedPdf {
+        pdf: dir.join(pdf_name),
+        dir,
+        converter: CONVERTERS[0],
+    };
+
+    let profile = converted.dir.join("profile");
+    let out_dir = converted.dir.clone();
+    let stderr_path = converted.dir.join("stderr.log");
+    let (converter, status) = run_converter(&stderr_path, &is_cancelled, |command| {
+        command
+            .arg(format!("-env:UserInstallation={}", file_url(&profile)))
+            .args([
+                "--headless",
+                "--norestore",
+                "--convert-to",
+                "pdf",
+                "--outdir",
+            ])
+            .arg(&out_dir)
+            .arg(path);
+    })?;
+    converted.converter = converter;
+
+    if !status.success() || !converted.pdf.exists() {
+        let stderr = fs::read_to_string(&stderr_path).unwrap_or_default();
+        return Err(AppError::PdfError(format!(
+            "Failed to convert {} to PDF: {}",
+            path.display(),
+            stderr.trim()
+        )));
+    }
+
+    Ok(converted)
+}
+
+/// Run the first LibreOffice executable found on PATH, writing its stderr
+/// to `stderr_path` so a chatty process cannot block on a full pipe
+fn run_converter(
+    stderr_path: &Path,
+    is_cancelled: &dyn Fn() -> bool,
+    configure: impl Fn(&mut Command),
+) -> Result<(&'static str, ExitStatus)> {
+    for converter in CONVERTERS {
+        let mut command = Command::new(converter);
+        configure(&mut command);
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(File::create(stderr_path)?);
+
+        match command.spawn() {
+            Ok(mut child) => {
+                let deadline = Instant::now() + CONVERT_TIMEOUT;
+                loop {
+                    if let Some(status) = child.try_wait()? {
+                        return Ok((converter, status));
+                    }
+                    let cancelled = is_cancelled();
+                    if cancelled || Instant::now() >= deadline {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        let reason = if cancelled {
+                            "was cancelled".to_string()
+                        } else {
+                            format!("timed out after {} seconds", CONVERT_TIMEOUT.as_secs())
+                        };
+                        return Err(AppError::PdfError(format!(
+                            "Conversion with {} {}",
+                            converter, reason
+                        )));
+                    }
+                    thread::sleep(CONVERT_POLL_INTERVAL);
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => continue,
+            Err(e) => {
+                return Err(AppError::PdfError(format!(
+                    "Failed to run {}: {}",
+                    converter, e
+                )))
+            }
+        }
+    }
+
+    Err(AppError::PdfError(
+        "LibreOffice not found. Install it and make sure soffice is on PATH to ingest office documents"
+            .to_string(),
+    ))
+}
+
+/// `file://` URL of a local path, as LibreOffice expects for `-env:` settings.
+/// Bytes outside the unreserved set are percent-encoded, so paths with
+/// spaces, `#` or `%` survive.
+fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut url = String::from(if path.starts_with('/') {
+        "file://"
+    } else {
+        "file:///"
+    });
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => {
+                url.push(byte as char)
+            }
+            _ => url.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    url
+}
@@ -0,0 +1,56 @@
+use crate::error::{AppError, Result};
+
+/// Parse a page selection such as "1-10,25,40-" into the sorted, distinct
+/// page numbers it selects from a PDF with `page_count` pages.
+///
+/// Ranges are inclusive; "40-" runs to the last page and "-5" starts at
+/// the first. Whitespace is ignored and overlapping ranges are merged.
+pub fn parse_page_ranges(spec: &str, page_count: i32) -> Result<Vec<i32>> {
+    let mut pages = Vec::new();
+
+    for part in spec
+        .split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        let invalid = || AppError::Custom(format!("Invalid page range \"{}\"", part));
+        let parse = |number: &str| number.trim().parse::<i32>().map_err(|_| invalid());
+
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => {
+                let first = match first.trim() {
+                    "" => 1,
+                    first => parse(first)?,
+                };
+                let last = match last.trim() {
+                    "" => page_count,
+                    last => parse(last)?,
+                };
+                (first, last)
+            }
+            None => {
+                let page = parse(part)?;
+                (page, page)
+            }
+        };
+
+        if first < 1 || first > last {
+            return Err(invalid());
+        }
+        if last > page_count {
+            return Err(AppError::Custom(format!(
+                "Page range \"{}\" is out of bounds: the PDF has {} pages",
+                part, page_count
+            )));
+        }
+        pages.extend(first..=last);
+    }
+
+    if pages.is_empty() {
+        return Err(AppError::Custom("No pages selected".to_string()));
+    }
+
+    pages.sort_unstable();
+    pages.dedup();
+    Ok(pages)
+}
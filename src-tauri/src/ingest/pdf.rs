@@ -1,159 +1,224 @@
-use std::fs;
-use std::path::Path;
-use std::process::Command;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::thread;
 
-use crate::error::{AppError, Result};
-
-/// Monotonic counter to ensure unique temp file names across concurrent calls
-static RENDER_COUNTER: AtomicU64 = AtomicU64::new(0);
-
-use super::types::PdfMetadata;
-
-/// Process a PDF file using poppler's pdftoppm and pdfinfo commands
-pub fn process_pdf(path: &Path) -> Result<PdfProcessingResult> {
-    // Get PDF info (page count, metadata)
-    let metadata = get_pdf_info(path)?;
-    let page_count = metadata.page_count;
+use image::codecs::jpeg::JpegEncoder;
+use image::{DynamicImage, ImageFormat};
+use tokio::sync::mpsc;
+use tokio::task::spawn_blocking;
 
-    // Render all pages to PNG
-    let mut pages = Vec::with_capacity(page_count as usize);
-    for page_num in 1..=page_count {
-        let png_bytes = render_page_to_png(path, page_num)?;
-        pages.push(png_bytes);
-    }
+use crate::db::ImageInfo;
+use crate::error::{AppError, Result};
 
-    Ok(PdfProcessingResult {
-        page_count,
-        metadata: PdfMetadata {
-            title: metadata.title,
-            author: metadata.author,
-        },
-        pages,
-    })
-}
+use super::fingerprint::{fingerprint_page, PageFingerprint};
+use super::imaging::{decode_image, image_info, make_thumbnails, Thumbnail};
+use super::layout::{detect_region_candidates, RegionCandidate};
+use super::types::{
+    BlankPagePolicy, IngestOptions, OutlineEntry, PdfMetadata, RenderFormat, RenderOptions,
+};
 
-/// Result of processing a PDF file
-pub struct PdfProcessingResult {
+/// Page count, metadata and navigation structure of a PDF file
+pub struct PdfInfo {
     pub page_count: i32,
     pub metadata: PdfMetadata,
-    pub pages: Vec<Vec<u8>>,
+    pub outline: Vec<OutlineEntry>,
+    /// Logical label of every page in order; empty when the PDF defines none
+    pub page_labels: Vec<String>,
 }
 
-struct PdfInfo {
-    page_count: i32,
-    title: Option<String>,
-    author: Option<String>,
+/// An encoded page image produced by `PdfRenderer::render_page`
+pub struct PageImage {
+    pub bytes: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    /// Effective resolution, below the requested one when `max_dimension` downscaled the page
+    pub dpi: u32,
 }
 
-/// Get PDF metadata using pdfinfo command
-fn get_pdf_info(path: &Path) -> Result<PdfInfo> {
-    let output = Command::new("pdfinfo").arg(path).output().map_err(|e| {
-        AppError::PdfError(format!(
-            "Failed to run pdfinfo: {}. Is poppler installed?",
-            e
-        ))
-    })?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::PdfError(format!("pdfinfo failed: {}", stderr)));
-    }
+/// A backend that reads and rasterizes PDFs. Render workers share one
+/// instance, so implementations must be usable from several threads.
+pub trait PdfRenderer: Send + Sync {
+    /// Name and version recorded as the renderer of page images
+    fn name(&self) -> &str;
+
+    /// Read page count, info dictionary, outline and page labels.
+    ///
+    /// Encrypted files need `password`, either the user or the owner password.
+    /// Fails with `PdfPasswordRequired` or `PdfWrongPassword` when it is
+    /// missing or wrong, and with `PdfCorrupt` when the file cannot be opened.
+    fn read_info(&self, path: &Path, password: Option<&str>) -> Result<PdfInfo>;
+
+    /// Open a PDF to render and read several of its pages, failing like `read_info`
+    fn open<'a>(
+        &'a self,
+        path: &'a Path,
+        password: Option<&'a str>,
+    ) -> Result<Box<dyn OpenedPdf + 'a>>;
+}
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+/// A PDF opened by `PdfRenderer::open`, shared by the render workers
+pub trait OpenedPdf: Send + Sync {
+    /// Render a single page according to `options`
+    fn render_page(&self, page_num: i32, options: &RenderOptions) -> Result<PageImage>;
 
-    let mut page_count = 0;
-    let mut title = None;
-    let mut author = None;
+    /// Extract the text layer of a single page.
+    /// Returns an empty string for pages without text (e.g. scanned images).
+    fn extract_text(&self, page_num: i32) -> Result<String>;
+}
 
-    for line in stdout.lines() {
-        if let Some((key, value)) = line.split_once(':') {
-            let key = key.trim();
-            let value = value.trim();
+/// The renderer chosen at build time: PDFium in-process with the `pdfium`
+/// cargo feature, poppler's command line tools otherwise
+pub fn renderer() -> &'static dyn PdfRenderer {
+    #[cfg(feature = "pdfium")]
+    {
+        &super::pdfium::PdfiumRenderer
+    }
+    #[cfg(not(feature = "pdfium"))]
+    {
+        &super::poppler::PopplerRenderer
+    }
+}
 
-            match key {
-                "Pages" => {
-                    page_count = value.parse().unwrap_or(0);
-                }
-                "Title" => {
-                    if !value.is_empty() {
-                        title = Some(value.to_string());
-                    }
-                }
-                "Author" => {
-                    if !value.is_empty() {
-                        author = Some(value.to_string());
-                    }
+/// A single rendered page, handed over by `stream_pages`
+pub struct RenderedPage {
+    pub page_num: i32,
+    pub bytes: Vec<u8>,
+    /// Dimensions, resolution, size, hash and renderer of `bytes`
+    pub image: ImageInfo,
+    /// Text layer of the page, when text extraction was requested
+    pub text: Option<String>,
+    /// Automatically detected regions, when region detection was requested
+    pub regions: Vec<RegionCandidate>,
+    /// Thumbnails of the page image, one per `options.thumbnail_sizes`
+    pub thumbnails: Vec<Thumbnail>,
+    /// Perceptual hash and ink coverage, when `options.fingerprint_pages()`
+    pub fingerprint: Option<PageFingerprint>,
+}
+
+/// Render the pages `page_nums` on up to `options.render_concurrency()`
+/// worker threads, extracting their text layer when `options.extract_text`.
+///
+/// The PDF is opened once for all pages. Pages are rendered in waves of
+/// `concurrency` pages and delivered in `page_num` order through a bounded
+/// channel, so at most two waves of rendered pages are held in memory at
+/// once. Rendering stops after the first error, or as soon as the receiver
+/// is dropped.
+pub fn stream_pages(
+    path: PathBuf,
+    password: Option<String>,
+    page_nums: Vec<i32>,
+    options: IngestOptions,
+) -> mpsc::Receiver<Result<RenderedPage>> {
+    let concurrency = options.render_concurrency();
+    let (tx, rx) = mpsc::channel(concurrency);
+
+    spawn_blocking(move || {
+        let document = match renderer().open(&path, password.as_deref()) {
+            Ok(document) => document,
+            Err(e) => {
+                let _ = tx.blocking_send(Err(e));
+                return;
+            }
+        };
+
+        for wave in page_nums.chunks(concurrency) {
+            let results: Vec<Result<RenderedPage>> = thread::scope(|scope| {
+                let workers: Vec<_> = wave
+                    .iter()
+                    .map(|&page_num| {
+                        let (document, options) = (&*document, &options);
+                        scope.spawn(move || process_page(document, page_num, options))
+                    })
+                    .collect();
+
+                workers
+                    .into_iter()
+                    .map(|worker| {
+                        worker.join().unwrap_or_else(|_| {
+                            Err(AppError::PdfError("Render worker panicked".to_string()))
+                        })
+                    })
+                    .collect()
+            });
+
+            for result in results {
+                let failed = result.is_err();
+
+                // A closed channel means the ingestion was aborted
+                if tx.blocking_send(result).is_err() || failed {
+                    return;
                 }
-                _ => {}
             }
         }
-    }
+    });
 
-    if page_count == 0 {
-        return Err(AppError::PdfError(
-            "Could not determine page count".to_string(),
-        ));
-    }
-
-    Ok(PdfInfo {
-        page_count,
-        title,
-        author,
-    })
+    rx
 }
 
-/// Render a single page to PNG bytes at 150 DPI using pdftoppm
-pub fn render_page_to_png(path: &Path, page_num: i32) -> Result<Vec<u8>> {
-    // Create a unique temporary file prefix per call to avoid races
-    let temp_dir = std::env::temp_dir();
-    let counter = RENDER_COUNTER.fetch_add(1, Ordering::Relaxed);
-    let output_prefix = temp_dir.join(format!("autorag_page_{}_{}", std::process::id(), counter));
-
-    let output = Command::new("pdftoppm")
-        .args([
-            "-png", // Output PNG format
-            "-r",
-            "150", // 150 DPI
-            "-f",
-            &page_num.to_string(), // First page
-            "-l",
-            &page_num.to_string(), // Last page (same = single page)
-            "-singlefile",         // Don't add page number suffix
-        ])
-        .arg(path)
-        .arg(&output_prefix)
-        .output()
-        .map_err(|e| {
-            AppError::PdfError(format!(
-                "Failed to run pdftoppm: {}. Is poppler installed?",
-                e
-            ))
-        })?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        return Err(AppError::PdfError(format!(
-            "pdftoppm failed on page {}: {}",
-            page_num, stderr
-        )));
-    }
+/// Render one page and, if requested, extract its text layer, detect regions
+/// and make thumbnails
+fn process_page(
+    document: &dyn OpenedPdf,
+    page_num: i32,
+    options: &IngestOptions,
+) -> Result<RenderedPage> {
+    let PageImage {
+        bytes,
+        width,
+        height,
+        dpi,
+    } = document.render_page(page_num, &options.render)?;
+    let image = image_info(&bytes, (width, height), Some(dpi), Some(renderer().name()));
+    let text = if options.extract_text {
+        Some(document.extract_text(page_num)?)
+    } else {
+        None
+    };
+
+    let mut regions = Vec::new();
+    let mut thumbnails = Vec::new();
+    let mut fingerprint = None;
+    if options.detect_regions || !options.thumbnail_sizes.is_empty() || options.fingerprint_pages()
+    {
+        let img = decode_image(&bytes)?;
+        if options.fingerprint_pages() {
+            fingerprint = Some(fingerprint_page(&img, options.blank_threshold));
+        }
 
-    // Read the output file (pdftoppm adds .png extension)
-    let output_file = format!("{}.png", output_prefix.display());
-    let png_bytes = fs::read(&output_file).map_err(|e| {
-        AppError::PdfError(format!("Failed to read rendered page {}: {}", page_num, e))
-    })?;
+        // Skipped blank pages are never stored, so there is nothing to derive
+        let skipped = options.blank_pages == BlankPagePolicy::Skip
+            && fingerprint.as_ref().is_some_and(|f| f.blank);
+        if !skipped {
+            if options.detect_regions {
+                regions = detect_region_candidates(&img, options.render.format.mimetype())?;
+            }
+            thumbnails = make_thumbnails(&img, &options.thumbnail_sizes)?;
+        }
+    }
 
-    // Clean up the temporary file
-    let _ = fs::remove_file(&output_file);
+    Ok(RenderedPage {
+        page_num,
+        bytes,
+        image,
+        text,
+        regions,
+        thumbnails,
+        fingerprint,
+    })
+}
 
-    if png_bytes.is_empty() {
-        return Err(AppError::PdfError(format!(
-            "pdftoppm produced empty output for page {}",
-            page_num
-        )));
+/// Encode a rendered page in `options.format`
+pub(super) fn encode_page(img: &DynamicImage, options: &RenderOptions) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    match options.format {
+        RenderFormat::Png => img
+            .write_to(&mut Cursor::new(&mut out), ImageFormat::Png)
+            .map_err(|e| AppError::ImageError(format!("Failed to encode as PNG: {}", e)))?,
+        RenderFormat::Jpeg => {
+            JpegEncoder::new_with_quality(&mut out, options.jpeg_quality.clamp(1, 100))
+                .encode_image(img)
+                .map_err(|e| AppError::ImageError(format!("Failed to encode as JPEG: {}", e)))?
+        }
     }
-
-    Ok(png_bytes)
+    Ok(out)
 }
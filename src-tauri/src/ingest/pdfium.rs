@@ -0,0 +1,288 @@
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use image::{DynamicImage, RgbaImage};
+use pdfium_render::prelude::*;
+
+use crate::error::{AppError, Result};
+
+use super::pdf::{encode_page, OpenedPdf, PageImage, PdfInfo, PdfRenderer};
+use super::structure::read_pdf_structure;
+use super::types::{PageSize, PdfMetadata, RenderOptions};
+
+/// PDFium is not thread-safe; render workers take turns
+static PDFIUM_LOCK: Mutex<()> = Mutex::new(());
+
+/// The PDFium library, bound on first use and kept for the process lifetime
+static PDFIUM: OnceLock<Pdfium> = OnceLock::new();
+
+/// Renders in-process with PDFium, without spawning processes or writing
+/// temp files. The PDFium library is loaded from the executable's directory,
+/// or else from the system library path.
+pub struct PdfiumRenderer;
+
+impl PdfRenderer for PdfiumRenderer {
+    fn name(&self) -> &str {
+        "PDFium"
+    }
+
+    fn read_info(&self, path: &Path, password: Option<&str>) -> Result<PdfInfo> {
+        let (page_count, metadata) = with_document(path, password, |document| {
+            let pages = document.pages();
+            let first_page = pages.get(0).ok();
+            let tags = document.metadata();
+            let tag = |tag_type| {
+                tags.get(tag_type)
+                    .map(|tag| tag.value().trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+
+            Ok((
+                pages.len() as i32,
+                PdfMetadata {
+                    title: tag(PdfDocumentMetadataTagType::Title),
+                    author: tag(PdfDocumentMetadataTagType::Author),
+                    subject: tag(PdfDocumentMetadataTagType::Subject),
+                    keywords: tag(PdfDocumentMetadataTagType::Keywords),
+                    creator: tag(PdfDocumentMetadataTagType::Creator),
+                    producer: tag(PdfDocumentMetadataTagType::Producer),
+                    creation_date: tag(PdfDocumentMetadataTagType::CreationDate)
+                        .map(|date| pdf_date_to_iso(&date)),
+                    mod_date: tag(PdfDocumentMetadataTagType::ModificationDate)
+                        .map(|date| pdf_date_to_iso(&date)),
+                    pdf_version: None,
+                    page_size: first_page.map(|page| PageSize {
+                        width: page.width().value as f64,
+                        height: page.height().value as f64,
+                        name: None,
+                    }),
+                },
+            ))
+        })?;
+
+        if page_count == 0 {
+            return Err(AppError::PdfError(
+                "Could not determine page count".to_string(),
+            ));
+        }
+
+        // Outline and page labels come from the same parser as with poppler
+        let structure = read_pdf_structure(path);
+
+        Ok(PdfInfo {
+            page_count,
+            metadata,
+            outline: structure.outline,
+            page_labels: structure.page_labels,
+        })
+    }
+
+    fn open<'a>(
+        &'a self,
+        path: &'a Path,
+        password: Option<&'a str>,
+    ) -> Result<Box<dyn OpenedPdf + 'a>> {
+        let _lock = PDFIUM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let document = load_document(path, password)?;
+        Ok(Box::new(PdfiumDocument {
+            document: Some(document),
+        }))
+    }
+}
+
+/// A document parsed once and rendered from page by page
+struct PdfiumDocument<'a> {
+    /// Only taken when dropping, so the document is closed under the lock
+    document: Option<PdfDocument<'a>>,
+}
+
+impl Drop for PdfiumDocument<'_> {
+    fn drop(&mut self) {
+        let _lock = PDFIUM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        drop(self.document.take());
+    }
+}
+
+impl OpenedPdf for PdfiumDocument<'_> {
+    fn render_page(&self, page_num: i32, options: &RenderOptions) -> Result<PageImage> {
+        let (img, scale) = self.with_page(page_num, |page| {
+            // Render straight at the size `max_dimension` allows instead of
+            // downscaling afterwards
+            let mut scale = options.dpi as f32 / 72.0;
+            let longest = page.width().value.max(page.height().value) * scale;
+            if let Some(max_dimension) = options.max_dimension {
+                if longest > max_dimension as f32 {
+                    scale *= max_dimension as f32 / longest;
+                }
+            }
+
+            let config = PdfRenderConfig::new().scale_page_by_factor(scale);
+            let bitmap = page.render_with_config(&config).map_err(|e| {
+                AppError::PdfError(format!("PDFium failed on page {}: {:?}", page_num, e))
+            })?;
+            Ok((bitmap_to_image(&bitmap, page_num)?, scale))
+        })?;
+
+        let img = if options.grayscale {
+            DynamicImage::ImageLuma8(img.to_luma8())
+        } else {
+            DynamicImage::ImageRgb8(img.to_rgb8())
+        };
+
+        Ok(PageImage {
+            bytes: encode_page(&img, options)?,
+            width: img.width(),
+            height: img.height(),
+            dpi: (scale * 72.0).round() as u32,
+        })
+    }
+
+    fn extract_text(&self, page_num: i32) -> Result<String> {
+        self.with_page(page_num, |page| {
+            let text = page.text().map_err(|e| {
+                AppError::PdfError(format!(
+                    "PDFium failed to read the text of page {}: {:?}",
+                    page_num, e
+                ))
+            })?;
+            Ok(text.all().trim().to_string())
+        })
+    }
+}
+
+impl PdfiumDocument<'_> {
+    /// Run `f` on page `page_num` (1-based) while holding the lock
+    fn with_page<T>(&self, page_num: i32, f: impl FnOnce(&PdfPage) -> Result<T>) -> Result<T> {
+        let _lock = PDFIUM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let missing = || AppError::PdfError(format!("Page {} does not exist", page_num));
+        let index = (page_num - 1).try_into().map_err(|_| missing())?;
+        let document = self
+            .document
+            .as_ref()
+            .ok_or_else(|| AppError::PdfError("PDF document is closed".to_string()))?;
+        let page = document.pages().get(index).map_err(|_| missing())?;
+        f(&page)
+    }
+}
+
+/// Open `path` with PDFium and run `f` on the document while holding the lock
+fn with_document<T>(
+    path: &Path,
+    password: Option<&str>,
+    f: impl FnOnce(&PdfDocument) -> Result<T>,
+) -> Result<T> {
+    let _lock = PDFIUM_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // Bound after the lock so the document is closed before it is released
+    let document = load_document(path, password)?;
+    let result = f(&document);
+    drop(document);
+    result
+}
+
+/// Parse `path` with PDFium. Callers hold the lock.
+fn load_document<'a>(path: &Path, password: Option<&'a str>) -> Result<PdfDocument<'a>> {
+    let password = password.filter(|password| !password.is_empty());
+    pdfium()?
+        .load_pdf_from_file(path, password)
+        .map_err(|e| open_error(e, password))
+}
+
+/// The bound PDFium library. Callers hold the lock, so it is bound only once;
+/// a failed attempt is retried on the next call.
+fn pdfium() -> Result<&'static Pdfium> {
+    if let Some(pdfium) = PDFIUM.get() {
+        return Ok(pdfium);
+    }
+    let pdfium = bind_pdfium()?;
+    Ok(PDFIUM.get_or_init(|| pdfium))
+}
+
+fn bind_pdfium() -> Result<Pdfium> {
+    let exe_dir = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| "./".to_string());
+
+    let bindings = Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(&exe_dir))
+        .or_else(|_| Pdfium::bind_to_system_library())
+        .map_err(|e| {
+            AppError::PdfError(format!(
+                "Failed to load the PDFium library: {:?}. Is PDFium installed?",
+                e
+            ))
+        })?;
+    Ok(Pdfium::new(bindings))
+}
+
+/// Classify why PDFium could not open a PDF
+fn open_error(error: PdfiumError, password: Option<&str>) -> AppError {
+    match error {
+        PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::PasswordError) => {
+            match password {
+                Some(_) => AppError::PdfWrongPassword,
+                None => AppError::PdfPasswordRequired,
+            }
+        }
+        PdfiumError::PdfiumLibraryInternalError(PdfiumInternalError::FormatError) => {
+            AppError::PdfCorrupt("PDFium could not parse the file".to_string())
+        }
+        error => AppError::PdfError(format!("PDFium failed to open the PDF: {:?}", error)),
+    }
+}
+
+/// Convert PDFium's BGRA pixels to an image
+fn bitmap_to_image(bitmap: &PdfBitmap, page_num: i32) -> Result<DynamicImage> {
+    let (width, height) = (bitmap.width() as u32, bitmap.height() as u32);
+
+    let mut pixels = bitmap.as_raw_bytes();
+    for pixel in pixels.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+
+    RgbaImage::from_raw(width, height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| {
+            AppError::PdfError(format!(
+                "PDFium returned a malformed bitmap for page {}",
+                page_num
+            ))
+        })
+}
+
+/// Convert a PDF date such as "D:20240131143000+01'00'" to ISO 8601,
+/// matching what pdfinfo reports. Unparsable dates are kept as they are.
+fn pdf_date_to_iso(raw: &str) -> String {
+    let date = raw.strip_prefix("D:").unwrap_or(raw);
+    let digits: String = date.chars().take_while(char::is_ascii_digit).collect();
+    if digits.len() < 4 {
+        return raw.to_string();
+    }
+
+    // Missing fields default to the start of the year, month or day
+    let field =
+        |start: usize, default: &'static str| digits.get(start..start + 2).unwrap_or(default);
+    let mut iso = format!(
+        "{}-{}-{}T{}:{}:{}",
+        &digits[..4],
+        field(4, "01"),
+        field(6, "01"),
+        field(8, "00"),
+        field(10, "00"),
+        field(12, "00"),
+    );
+
+    let zone = &date[digits.len()..];
+    match zone.chars().next() {
+        Some('Z') => iso.push('Z'),
+        Some(sign @ ('+' | '-')) => {
+            let offset: String = zone[1..].chars().filter(char::is_ascii_digit).collect();
+            if offset.len() >= 2 {
+                let minutes = offset.get(2..4).unwrap_or("00");
+                iso.push_str(&format!("{}{}:{}", sign, &offset[..2], minutes));
+            }
+        }
+        _ => {}
+    }
+    iso
+}
@@ -0,0 +1,353 @@
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use image::imageops::FilterType;
+
+use crate::error::{AppError, Result};
+
+use super::pdf::{encode_page, OpenedPdf, PageImage, PdfInfo, PdfRenderer};
+use super::structure::read_pdf_structure;
+use super::types::{PageSize, PdfMetadata, RenderFormat, RenderOptions};
+
+/// Monotonic counter to ensure unique temp file names across concurrent calls
+static RENDER_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Renders with poppler's pdfinfo, pdftoppm and pdftotext commands, which
+/// must be on PATH. Every call spawns a process; pages go through temp files.
+pub struct PopplerRenderer;
+
+impl PdfRenderer for PopplerRenderer {
+    fn name(&self) -> &str {
+        pdftoppm_version()
+    }
+
+    fn read_info(&self, path: &Path, password: Option<&str>) -> Result<PdfInfo> {
+        read_pdf_info(path, password)
+    }
+
+    fn open<'a>(
+        &'a self,
+        path: &'a Path,
+        password: Option<&'a str>,
+    ) -> Result<Box<dyn OpenedPdf + 'a>> {
+        Ok(Box::new(PopplerDocument { path, password }))
+    }
+}
+
+/// Every page is read by a fresh process, so opening only keeps the arguments
+struct PopplerDocument<'a> {
+    path: &'a Path,
+    password: Option<&'a str>,
+}
+
+impl OpenedPdf for PopplerDocument<'_> {
+    fn render_page(&self, page_num: i32, options: &RenderOptions) -> Result<PageImage> {
+        render_page(self.path, self.password, page_num, options)
+    }
+
+    fn extract_text(&self, page_num: i32) -> Result<String> {
+        extract_page_text(self.path, self.password, page_num)
+    }
+}
+
+/// Read the info dictionary with pdfinfo, and the outline and page labels
+fn read_pdf_info(path: &Path, password: Option<&str>) -> Result<PdfInfo> {
+    let output = Command::new("pdfinfo")
+        .arg("-isodates")
+        .args(password_args(password))
+        .arg(path)
+        .output()
+        .map_err(|e| {
+            AppError::PdfError(format!(
+                "Failed to run pdfinfo: {}. Is poppler installed?",
+                e
+            ))
+        })?;
+
+    if !output.status.success() {
+        return Err(open_error(&output, password));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let mut page_count = 0;
+    let mut metadata = PdfMetadata::default();
+
+    for line in stdout.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let key = key.trim();
+            let value = value.trim();
+            let text = (!value.is_empty()).then(|| value.to_string());
+
+            match key {
+                "Pages" => {
+                    page_count = value.parse().unwrap_or(0);
+                }
+                "Title" => metadata.title = text,
+                "Author" => metadata.author = text,
+                "Subject" => metadata.subject = text,
+                "Keywords" => metadata.keywords = text,
+                "Creator" => metadata.creator = text,
+                "Producer" => metadata.producer = text,
+                "CreationDate" => metadata.creation_date = text,
+                "ModDate" => metadata.mod_date = text,
+                "PDF version" => metadata.pdf_version = text,
+                "Page size" => metadata.page_size = parse_page_size(value),
+                _ => {}
+            }
+        }
+    }
+
+    if page_count == 0 {
+        return Err(AppError::PdfError(
+            "Could not determine page count".to_string(),
+        ));
+    }
+
+    let structure = read_pdf_structure(path);
+
+    Ok(PdfInfo {
+        page_count,
+        metadata,
+        outline: structure.outline,
+        page_labels: structure.page_labels,
+    })
+}
+
+/// Poppler arguments opening an encrypted PDF with `password`. Poppler tries
+/// the owner password first and then the user password, so passing it as
+/// both accepts either. The tools take no other way to supply it, so it is
+/// visible in the process list while they run; the `pdfium` feature opens
+/// PDFs in-process instead.
+fn password_args(password: Option<&str>) -> Vec<String> {
+    match password.filter(|password| !password.is_empty()) {
+        Some(password) => vec![
+            "-opw".to_string(),
+            password.to_string(),
+            "-upw".to_string(),
+            password.to_string(),
+        ],
+        None => Vec::new(),
+    }
+}
+
+/// Classify why poppler could not open a PDF
+fn open_error(output: &Output, password: Option<&str>) -> AppError {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    if stderr.contains("Incorrect password") {
+        return match password.filter(|password| !password.is_empty()) {
+            Some(_) => AppError::PdfWrongPassword,
+            None => AppError::PdfPasswordRequired,
+        };
+    }
+    // Exit code 1 is poppler's "error opening a PDF file"; a missing or
+    // unreadable file is reported the same way but is not a damaged PDF
+    if output.status.code() == Some(1) && !stderr.contains("Couldn't open file") {
+        return AppError::PdfCorrupt(stderr.trim().to_string());
+    }
+    AppError::PdfError(format!("pdfinfo failed: {}", stderr))
+}
+
+/// Parse pdfinfo's page size, e.g. "595.276 x 841.89 pts (A4)"
+fn parse_page_size(value: &str) -> Option<PageSize> {
+    let (dimensions, rest) = value.split_once("pts")?;
+    let (width, height) = dimensions.split_once('x')?;
+    let name = rest
+        .trim()
+        .strip_prefix('(')
+        .and_then(|name| name.strip_suffix(')'))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty());
+
+    Some(PageSize {
+        width: width.trim().parse().ok()?,
+        height: height.trim().parse().ok()?,
+        name,
+    })
+}
+
+/// Version of the pdftoppm in use, e.g. "pdftoppm 24.02.0", detected once.
+/// Plain "pdftoppm" when the version cannot be determined.
+fn pdftoppm_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+
+    VERSION.get_or_init(|| {
+        // pdftoppm prints its version banner to stderr
+        let version = Command::new("pdftoppm")
+            .arg("-v")
+            .output()
+            .ok()
+            .and_then(|output| {
+                let banner = String::from_utf8_lossy(&output.stderr).into_owned();
+                banner
+                    .lines()
+                    .next()
+                    .and_then(|line| line.strip_prefix("pdftoppm version "))
+                    .map(|version| version.trim().to_string())
+            });
+
+        match version {
+            Some(version) => format!("pdftoppm {}", version),
+            None => "pdftoppm".to_string(),
+        }
+    })
+}
+
+/// Render a single page with pdftoppm according to `options`
+fn render_page(
+    path: &Path,
+    password: Option<&str>,
+    page_num: i32,
+    options: &RenderOptions,
+) -> Result<PageImage> {
+    // Create a unique temporary file prefix per call to avoid races
+    let temp_dir = std::env::temp_dir();
+    let counter = RENDER_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let output_prefix = temp_dir.join(format!("autorag_page_{}_{}", std::process::id(), counter));
+
+    let mut command = Command::new("pdftoppm");
+    command.args(password_args(password));
+    match options.format {
+        RenderFormat::Png => {
+            command.arg("-png");
+        }
+        RenderFormat::Jpeg => {
+            command.args([
+                "-jpeg".to_string(),
+                "-jpegopt".to_string(),
+                format!("quality={}", options.jpeg_quality.clamp(1, 100)),
+            ]);
+        }
+    }
+    if options.grayscale {
+        command.arg("-gray");
+    }
+
+    let output = command
+        .args([
+            "-r",
+            &options.dpi.to_string(),
+            "-f",
+            &page_num.to_string(), // First page
+            "-l",
+            &page_num.to_string(), // Last page (same = single page)
+            "-singlefile",         // Don't add page number suffix
+        ])
+        .arg(path)
+        .arg(&output_prefix)
+        .output()
+        .map_err(|e| {
+            AppError::PdfError(format!(
+                "Failed to run pdftoppm: {}. Is poppler installed?",
+                e
+            ))
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::PdfError(format!(
+            "pdftoppm failed on page {}: {}",
+            page_num, stderr
+        )));
+    }
+
+    // Read the output file (pdftoppm adds the format's extension)
+    let output_file = format!("{}.{}", output_prefix.display(), options.format.extension());
+    let bytes = fs::read(&output_file).map_err(|e| {
+        AppError::PdfError(format!("Failed to read rendered page {}: {}", page_num, e))
+    })?;
+
+    // Clean up the temporary file
+    let _ = fs::remove_file(&output_file);
+
+    if bytes.is_empty() {
+        return Err(AppError::PdfError(format!(
+            "pdftoppm produced empty output for page {}",
+            page_num
+        )));
+    }
+
+    let (width, height) = image_dimensions(&bytes)?;
+    let page = PageImage {
+        bytes,
+        width,
+        height,
+        dpi: options.dpi,
+    };
+
+    match options.max_dimension {
+        Some(max_dimension) if width.max(height) > max_dimension => {
+            fit_to_max_dimension(page, max_dimension, options)
+        }
+        _ => Ok(page),
+    }
+}
+
+/// Width and height of encoded image bytes, read from the header only
+fn image_dimensions(bytes: &[u8]) -> Result<(u32, u32)> {
+    image::io::Reader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| AppError::ImageError(format!("Failed to read image: {}", e)))?
+        .into_dimensions()
+        .map_err(|e| AppError::ImageError(format!("Failed to read image dimensions: {}", e)))
+}
+
+/// Downscale a rendered page so its longest side is at most `max_dimension`
+fn fit_to_max_dimension(
+    page: PageImage,
+    max_dimension: u32,
+    options: &RenderOptions,
+) -> Result<PageImage> {
+    let img = image::load_from_memory(&page.bytes)
+        .map_err(|e| AppError::ImageError(format!("Failed to decode rendered page: {}", e)))?;
+
+    let resized = img.resize(max_dimension, max_dimension, FilterType::Lanczos3);
+    let scale = resized.width() as f64 / img.width() as f64;
+
+    Ok(PageImage {
+        bytes: encode_page(&resized, options)?,
+        width: resized.width(),
+        height: resized.height(),
+        dpi: (page.dpi as f64 * scale).round() as u32,
+    })
+}
+
+/// Extract the text layer of a single page using pdftotext
+fn extract_page_text(path: &Path, password: Option<&str>, page_num: i32) -> Result<String> {
+    let output = Command::new("pdftotext")
+        .args(password_args(password))
+        .args([
+            "-enc",
+            "UTF-8",
+            "-f",
+            &page_num.to_string(),
+            "-l",
+            &page_num.to_string(),
+        ])
+        .arg(path)
+        .arg("-") // Write to stdout
+        .output()
+        .map_err(|e| {
+            AppError::PdfError(format!(
+                "Failed to run pdftotext: {}. Is poppler installed?",
+                e
+            ))
+        })?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AppError::PdfError(format!(
+            "pdftotext failed on page {}: {}",
+            page_num, stderr
+        )));
+    }
+
+    // pdftotext terminates every page with a form feed, which trim() strips
+    let text = String::from_utf8_lossy(&output.stdout);
+    Ok(text.trim().to_string())
+}
@@ -0,0 +1,104 @@
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::db::ImageInfo;
+use crate::error::{AppError, Result};
+
+use super::imaging::{decode_image, encode_image, image_info};
+
+/// Rectangle on a page in fractions (0.0-1.0) of the page width and height,
+/// measured from the top-left corner. Independent of the render resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Rectangle in pixels of a specific page image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl BoundingBox {
+    /// Ensure the box has a positive size and lies within the page
+    pub fn validate(&self) -> Result<()> {
+        let values = [self.x, self.y, self.width, self.height];
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err(AppError::ImageError(
+                "Bounding box values must be finite numbers".to_string(),
+            ));
+        }
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return Err(AppError::ImageError(
+                "Bounding box must have a positive width and height".to_string(),
+            ));
+        }
+        if self.x < 0.0 || self.y < 0.0 || self.x + self.width > 1.0 || self.y + self.height > 1.0 {
+            return Err(AppError::ImageError(
+                "Bounding box must lie within the page (fractions between 0 and 1)".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Convert to pixels of an image of the given size, covering at least one pixel
+    pub fn to_pixels(self, image_width: u32, image_height: u32) -> PixelRect {
+        let x = ((self.x * image_width as f64).floor() as u32).min(image_width.saturating_sub(1));
+        let y = ((self.y * image_height as f64).floor() as u32).min(image_height.saturating_sub(1));
+        let right = ((self.x + self.width) * image_width as f64).ceil() as u32;
+        let bottom = ((self.y + self.height) * image_height as f64).ceil() as u32;
+
+        PixelRect {
+            x,
+            y,
+            width: right.min(image_width).saturating_sub(x).max(1),
+            height: bottom.min(image_height).saturating_sub(y).max(1),
+        }
+    }
+}
+
+/// A region cropped out of a page image
+pub struct CroppedRegion {
+    pub bytes: Vec<u8>,
+    pub mimetype: &'static str,
+    pub rect: PixelRect,
+}
+
+impl CroppedRegion {
+    /// Describe the crop, inheriting resolution and renderer from the image it was cut from
+    pub fn image_info(&self, source: Option<&ImageInfo>) -> ImageInfo {
+        image_info(
+            &self.bytes,
+            (self.rect.width, self.rect.height),
+            source.and_then(|s| s.dpi),
+            source.and_then(|s| s.renderer.as_deref()),
+        )
+    }
+}
+
+/// Crop `bbox` out of an encoded page image, keeping the page's image format
+pub fn crop_region(contents: &[u8], mimetype: &str, bbox: &BoundingBox) -> Result<CroppedRegion> {
+    bbox.validate()?;
+
+    let img = decode_image(contents)?;
+    crop_image(&img, mimetype, bbox)
+}
+
+/// Crop `bbox` out of an already decoded page image and encode it as `mimetype`
+pub fn crop_image(img: &DynamicImage, mimetype: &str, bbox: &BoundingBox) -> Result<CroppedRegion> {
+    let rect = bbox.to_pixels(img.width(), img.height());
+    let cropped = img.crop_imm(rect.x, rect.y, rect.width, rect.height);
+    let (bytes, mimetype) = encode_image(&cropped, mimetype)?;
+
+    Ok(CroppedRegion {
+        bytes,
+        mimetype,
+        rect,
+    })
+}
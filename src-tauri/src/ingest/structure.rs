@@ -0,0 +1,326 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use lopdf::{decode_text_string, Dictionary, Document, Object, ObjectId};
+
+use super::types::OutlineEntry;
+
+/// Outlines deeper than this are cut off
+const MAX_OUTLINE_DEPTH: u32 = 32;
+/// Outlines with more bookmarks than this are truncated
+const MAX_OUTLINE_ENTRIES: usize = 10_000;
+/// Number and name trees nested deeper than this are not followed
+const MAX_TREE_DEPTH: u32 = 32;
+
+/// Navigation structure of a PDF that pdfinfo does not report
+#[derive(Debug, Clone, Default)]
+pub struct PdfStructure {
+    pub outline: Vec<OutlineEntry>,
+    /// Logical label of every page in order; empty when the PDF defines none
+    pub page_labels: Vec<String>,
+}
+
+/// Read the bookmark outline and page labels of a PDF.
+///
+/// Both are optional navigation aids: a file lopdf cannot parse, including
+/// one that needs a password to open, yields an empty structure instead of
+/// failing the import, since rendering does not depend on lopdf.
+/// Page contents and images are dropped while the file is parsed.
+pub fn read_pdf_structure(path: &Path) -> PdfStructure {
+    let Ok(doc) = Document::load_filtered(path, without_stream_contents) else {
+        return PdfStructure::default();
+    };
+    let Ok(catalog) = doc.catalog() else {
+        return PdfStructure::default();
+    };
+
+    let pages: HashMap<ObjectId, i32> = doc
+        .get_pages()
+        .into_iter()
+        .map(|(page_num, page_id)| (page_id, page_num as i32))
+        .collect();
+
+    PdfStructure {
+        outline: read_outline(&doc, catalog, &pages),
+        page_labels: read_page_labels(&doc, catalog, pages.len()),
+    }
+}
+
+/// Empty every stream except object streams, which hold the dictionaries the
+/// outline and page labels are read from. The outline, page labels and page
+/// tree never live in other streams, so only content and image data is lost.
+fn without_stream_contents(id: ObjectId, object: &mut Object) -> Option<(ObjectId, Object)> {
+    if let Object::Stream(stream) = object {
+        if !stream.dict.has_type(b"ObjStm") {
+            stream.set_content(Vec::new());
+        }
+    }
+    Some((id, object.clone()))
+}
+
+/// Follow references until reaching a direct object
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Object> {
+    doc.dereference(object).ok().map(|(_, object)| object)
+}
+
+fn get<'a>(doc: &'a Document, dict: &'a Dictionary, key: &[u8]) -> Option<&'a Object> {
+    dict.get(key).ok().and_then(|object| resolve(doc, object))
+}
+
+fn get_dict<'a>(doc: &'a Document, dict: &'a Dictionary, key: &[u8]) -> Option<&'a Dictionary> {
+    get(doc, dict, key).and_then(|object| object.as_dict().ok())
+}
+
+fn get_text(doc: &Document, dict: &Dictionary, key: &[u8]) -> Option<String> {
+    get(doc, dict, key).and_then(|object| decode_text_string(object).ok())
+}
+
+struct OutlineWalker<'a> {
+    doc: &'a Document,
+    pages: &'a HashMap<ObjectId, i32>,
+    named_destinations: HashMap<Vec<u8>, &'a Object>,
+    visited: HashSet<ObjectId>,
+    entries: Vec<OutlineEntry>,
+}
+
+fn read_outline(
+    doc: &Document,
+    catalog: &Dictionary,
+    pages: &HashMap<ObjectId, i32>,
+) -> Vec<OutlineEntry> {
+    let Some(outlines) = get_dict(doc, catalog, b"Outlines") else {
+        return Vec::new();
+    };
+
+    // Bookmarks may point to named destinations, declared either in the
+    // catalog's /Dests dictionary (PDF 1.1) or in the /Dests name tree
+    let mut named_destinations = HashMap::new();
+    if let Some(dests) = get_dict(doc, catalog, b"Dests") {
+        for (name, destination) in dests.iter() {
+            named_destinations.insert(name.clone(), destination);
+        }
+    }
+    if let Some(tree) = get_dict(doc, catalog, b"Names").and_then(|n| get_dict(doc, n, b"Dests")) {
+        collect_tree(doc, tree, b"Names", 0, &mut |key, value| {
+            if let Ok(name) = key.as_str() {
+                named_destinations.insert(name.to_vec(), value);
+            }
+        });
+    }
+
+    let mut walker = OutlineWalker {
+        doc,
+        pages,
+        named_destinations,
+        visited: HashSet::new(),
+        entries: Vec::new(),
+    };
+    if let Ok(first) = outlines.get(b"First") {
+        walker.walk(first, 1);
+    }
+    walker.entries
+}
+
+impl<'a> OutlineWalker<'a> {
+    /// Record the bookmark `item`, its children and its following siblings
+    fn walk(&mut self, item: &'a Object, level: u32) {
+        let mut next = Some(item);
+        while let Some(item) = next.take() {
+            if level > MAX_OUTLINE_DEPTH || self.entries.len() >= MAX_OUTLINE_ENTRIES {
+                return;
+            }
+            // Guard against malformed outlines that link back to themselves
+            if let Ok(id) = item.as_reference() {
+                if !self.visited.insert(id) {
+                    return;
+                }
+            }
+            let Some(node) = resolve(self.doc, item).and_then(|o| o.as_dict().ok()) else {
+                return;
+            };
+
+            let title = get_text(self.doc, node, b"Title").unwrap_or_default();
+            self.entries.push(OutlineEntry {
+                title: title.trim().to_string(),
+                level,
+                page_num: self.bookmark_page(node),
+            });
+
+            if let Ok(first) = node.get(b"First") {
+                self.walk(first, level + 1);
+            }
+            next = node.get(b"Next").ok();
+        }
+    }
+
+    /// Page a bookmark jumps to, through its /Dest or a GoTo action
+    fn bookmark_page(&self, node: &'a Dictionary) -> Option<i32> {
+        if let Some(dest) = get(self.doc, node, b"Dest") {
+            return self.destination_page(dest, 0);
+        }
+        let action = get_dict(self.doc, node, b"A")?;
+        match get(self.doc, action, b"S")?.as_name().ok()? {
+            b"GoTo" => self.destination_page(get(self.doc, action, b"D")?, 0),
+            _ => None,
+        }
+    }
+
+    /// Resolve an explicit destination `[page /XYZ ...]`, a named
+    /// destination, or a destination dictionary `<< /D [...] >>`
+    fn destination_page(&self, dest: &'a Object, depth: u32) -> Option<i32> {
+        if depth > 2 {
+            return None;
+        }
+        match resolve(self.doc, dest)? {
+            Object::Array(items) => {
+                let page_id = items.first()?.as_reference().ok()?;
+                self.pages.get(&page_id).copied()
+            }
+            Object::Name(name) | Object::String(name, _) => {
+                let target = self.named_destinations.get(name)?;
+                self.destination_page(target, depth + 1)
+            }
+            Object::Dictionary(dict) => self.destination_page(dict.get(b"D").ok()?, depth + 1),
+            _ => None,
+        }
+    }
+}
+
+/// Visit the key/value pairs of a number tree (`entries_key` /Nums) or
+/// name tree (/Names) in order
+fn collect_tree<'a>(
+    doc: &'a Document,
+    node: &'a Dictionary,
+    entries_key: &[u8],
+    depth: u32,
+    visit: &mut dyn FnMut(&'a Object, &'a Object),
+) {
+    if depth > MAX_TREE_DEPTH {
+        return;
+    }
+    if let Some(Object::Array(entries)) = get(doc, node, entries_key) {
+        for pair in entries.chunks_exact(2) {
+            if let (Some(key), Some(value)) = (resolve(doc, &pair[0]), resolve(doc, &pair[1])) {
+                visit(key, value);
+            }
+        }
+    }
+    if let Some(Object::Array(kids)) = get(doc, node, b"Kids") {
+        for kid in kids {
+            if let Some(kid) = resolve(doc, kid).and_then(|o| o.as_dict().ok()) {
+                collect_tree(doc, kid, entries_key, depth + 1, visit);
+            }
+        }
+    }
+}
+
+/// One /PageLabels range, applying from page index `start` onwards
+struct LabelRange {
+    start: i64,
+    style: Option<Vec<u8>>,
+    prefix: String,
+    first_number: i64,
+}
+
+fn read_page_labels(doc: &Document, catalog: &Dictionary, page_count: usize) -> Vec<String> {
+    let Some(tree) = get_dict(doc, catalog, b"PageLabels") else {
+        return Vec::new();
+    };
+
+    let mut ranges = Vec::new();
+    collect_tree(doc, tree, b"Nums", 0, &mut |key, value| {
+        let (Ok(start), Ok(label)) = (key.as_i64(), value.as_dict()) else {
+            return;
+        };
+        ranges.push(LabelRange {
+            start,
+            style: get(doc, label, b"S")
+                .and_then(|s| s.as_name().ok())
+                .map(<[u8]>::to_vec),
+            prefix: get_text(doc, label, b"P").unwrap_or_default(),
+            first_number: get(doc, label, b"St")
+                .and_then(|st| st.as_i64().ok())
+                .unwrap_or(1),
+        });
+    });
+    if ranges.is_empty() {
+        return Vec::new();
+    }
+    ranges.sort_by_key(|range| range.start);
+
+    (0..page_count as i64)
+        .map(|index| {
+            let range = ranges.iter().rev().find(|range| range.start <= index);
+            // /St comes from the file, so the number may not fit
+            let number = range.and_then(|range| {
+                index
+                    .checked_sub(range.start)
+                    .and_then(|offset| range.first_number.checked_add(offset))
+                    .map(|number| (range, number))
+            });
+            match number {
+                Some((range, number)) => page_label(range, number),
+                // Pages before the first range keep their physical number
+                None => (index + 1).to_string(),
+            }
+        })
+        .collect()
+}
+
+fn page_label(range: &LabelRange, number: i64) -> String {
+    let numeral = match range.style.as_deref() {
+        Some(b"D") => number.to_string(),
+        Some(b"R") => roman_numeral(number),
+        Some(b"r") => roman_numeral(number).to_lowercase(),
+        Some(b"A") => letter_numeral(number),
+        Some(b"a") => letter_numeral(number).to_lowercase(),
+        // Without a numbering style the label is the prefix alone
+        _ => String::new(),
+    };
+    format!("{}{}", range.prefix, numeral)
+}
+
+/// Largest number written in roman numerals; larger ones stay decimal
+const MAX_ROMAN_NUMERAL: i64 = 3999;
+/// Longest letter numeral (`ZZZZZZZZZZ`); larger numbers stay decimal
+const MAX_LETTER_REPEAT: i64 = 10;
+
+fn roman_numeral(mut number: i64) -> String {
+    if number <= 0 || number > MAX_ROMAN_NUMERAL {
+        return number.to_string();
+    }
+    const NUMERALS: [(i64, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+
+    let mut numeral = String::new();
+    for (value, symbol) in NUMERALS {
+        while number >= value {
+            numeral.push_str(symbol);
+            number -= value;
+        }
+    }
+    numeral
+}
+
+/// A to Z, then AA to ZZ, AAA to ZZZ and so on
+fn letter_numeral(number: i64) -> String {
+    if number <= 0 || (number - 1) / 26 >= MAX_LETTER_REPEAT {
+        return number.to_string();
+    }
+    let letter = (b'A' + ((number - 1) % 26) as u8) as char;
+    let repeat = ((number - 1) / 26 + 1) as usize;
+    letter.to_string().repeat(repeat)
+}
@@ -1,21 +1,36 @@
 use serde::{Deserialize, Serialize};
 
+use super::chunking::ChunkingStrategy;
+
 /// Progress update during PDF ingestion
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct IngestionProgress {
     pub current_page: i32,
     pub total_pages: i32,
-    pub phase: String, // "Reading", "Rendering", "Complete", "Failed"
+    pub phase: String, // "Converting", "Reading", "Rendering", "Complete", "Failed", "Cancelled"
     pub message: String,
+    /// Job the update belongs to, see `list_ingestion_jobs`
+    pub job_id: Option<u64>,
 }
 
 impl IngestionProgress {
+    pub fn converting(file_path: &str) -> Self {
+        Self {
+            current_page: 0,
+            total_pages: 0,
+            phase: "Converting".to_string(),
+            message: format!("Converting {} to PDF", file_path),
+            job_id: None,
+        }
+    }
+
     pub fn reading(total_pages: i32) -> Self {
         Self {
             current_page: 0,
             total_pages,
             phase: "Reading".to_string(),
             message: format!("Reading PDF ({} pages)", total_pages),
+            job_id: None,
         }
     }
 
@@ -25,6 +40,7 @@ impl IngestionProgress {
             total_pages,
             phase: "Rendering".to_string(),
             message: format!("Rendering page {} of {}", current_page, total_pages),
+            job_id: None,
         }
     }
 
@@ -34,6 +50,28 @@ impl IngestionProgress {
             total_pages,
             phase: "Complete".to_string(),
             message: format!("Successfully imported {} pages", total_pages),
+            job_id: None,
+        }
+    }
+
+    /// Progress of `ingest_directory`, counted in scanned items
+    pub fn batch_item(current_item: i32, total_items: i32) -> Self {
+        Self {
+            current_page: current_item,
+            total_pages: total_items,
+            phase: "Rendering".to_string(),
+            message: format!("Processed {} of {} items", current_item, total_items),
+            job_id: None,
+        }
+    }
+
+    pub fn batch_complete(total_items: i32) -> Self {
+        Self {
+            current_page: total_items,
+            total_pages: total_items,
+            phase: "Complete".to_string(),
+            message: format!("Processed {} items", total_items),
+            job_id: None,
         }
     }
 
@@ -43,6 +81,17 @@ impl IngestionProgress {
             total_pages: 0,
             phase: "Failed".to_string(),
             message,
+            job_id: None,
+        }
+    }
+
+    pub fn cancelled() -> Self {
+        Self {
+            current_page: 0,
+            total_pages: 0,
+            phase: "Cancelled".to_string(),
+            message: "Ingestion cancelled, no pages were imported".to_string(),
+            job_id: None,
         }
     }
 }
@@ -54,11 +103,233 @@ pub struct IngestionResult {
     pub document_id: i64,
     pub page_count: i32,
     pub image_chunk_count: i32,
+    pub chunk_count: i32,
+    /// Earlier document with the same source hash. Equals `document_id`
+    /// when the import was linked to it instead of repeated.
+    pub duplicate_of: Option<i64>,
+    /// Page numbers detected as blank, whether flagged or skipped
+    pub blank_pages: Vec<i32>,
 }
 
-/// Metadata extracted from PDF
-#[derive(Debug, Clone, Default)]
+/// Result of re-rendering a document with `rerender_document`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RerenderResult {
+    pub document_id: i64,
+    pub page_count: i32,
+    /// Page and region chunks whose contents were replaced
+    pub image_chunk_count: i32,
+}
+
+/// File extensions accepted for ingestion, grouped by how they are imported
+#[derive(Debug, Clone, Serialize)]
+pub struct SupportedFormats {
+    pub pdf: Vec<&'static str>,
+    /// Converted to PDF with LibreOffice
+    pub office: Vec<&'static str>,
+    /// The formats the image decoder was built with
+    pub images: Vec<&'static str>,
+}
+
+/// Info dictionary of a PDF as reported by pdfinfo, recorded as `doc_metadata.pdf_info`
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct PdfMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub author: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keywords: Option<String>,
+    /// Application that created the original document
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creator: Option<String>,
+    /// Application that produced the PDF
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub producer: Option<String>,
+    /// ISO 8601
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub creation_date: Option<String>,
+    /// ISO 8601
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mod_date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdf_version: Option<String>,
+    /// Size of the first page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<PageSize>,
+}
+
+/// Page size in PDF points (1/72 inch)
+#[derive(Debug, Clone, Serialize)]
+pub struct PageSize {
+    pub width: f64,
+    pub height: f64,
+    /// Paper format recognized by pdfinfo, e.g. "A4" or "letter"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+/// Bookmark in a PDF outline, recorded in `doc_metadata.outline`
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlineEntry {
+    pub title: String,
+    /// Nesting depth, 1 for top-level bookmarks
+    pub level: u32,
+    /// Physical page number the bookmark points to, if it points into this document
+    pub page_num: Option<i32>,
+}
+
+/// Image encoding used for rendered pages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderFormat {
+    #[default]
+    Png,
+    Jpeg,
+}
+
+impl RenderFormat {
+    pub fn mimetype(&self) -> &'static str {
+        match self {
+            RenderFormat::Png => "image/png",
+            RenderFormat::Jpeg => "image/jpeg",
+        }
+    }
+
+    /// File extension pdftoppm appends to its output
+    pub fn extension(&self) -> &'static str {
+        match self {
+            RenderFormat::Png => "png",
+            RenderFormat::Jpeg => "jpg",
+        }
+    }
+}
+
+/// How PDF pages are rasterized. Recorded in `page_metadata.render`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderOptions {
+    pub dpi: u32,
+    pub format: RenderFormat,
+    /// JPEG quality (1-100), ignored for PNG
+    pub jpeg_quality: u8,
+    pub grayscale: bool,
+    /// Longest side in pixels; larger renders are downscaled to fit
+    pub max_dimension: Option<u32>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        Self {
+            dpi: 150,
+            format: RenderFormat::Png,
+            jpeg_quality: 90,
+            grayscale: false,
+            max_dimension: None,
+        }
+    }
+}
+
+/// What ingestion does when the source file was already ingested,
+/// judged by `doc_metadata.source_sha256`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicatePolicy {
+    /// Fail with a duplicate error
+    #[default]
+    Refuse,
+    /// Skip the import and return the existing document
+    Link,
+    /// Import again, recording `duplicate_of` in the new document's metadata
+    Force,
+}
+
+/// What ingestion does with blank pages (see `IngestOptions::blank_threshold`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BlankPagePolicy {
+    /// Store blank pages like any other page
+    #[default]
+    Keep,
+    /// Store blank pages with `page_metadata.fingerprint.blank` set
+    Flag,
+    /// Do not store blank pages; the remaining pages keep their page numbers
+    Skip,
+}
+
+/// Options controlling how a document is ingested.
+/// Every field has a default, so callers only send what they want to change.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestOptions {
+    /// Maximum number of pages rendered in parallel (defaults to the number of CPU cores)
+    pub concurrency: Option<usize>,
+    pub render: RenderOptions,
+    /// Also store each page's text layer as `chunk` rows
+    pub extract_text: bool,
+    /// How extracted page text is split into chunks
+    pub chunking: ChunkingStrategy,
+    /// Detect figures, tables and text blocks and store them as candidate
+    /// region chunks flagged `"source": "auto"` for review
+    pub detect_regions: bool,
+    /// What to do when a document with the same source hash already exists
+    pub on_duplicate: DuplicatePolicy,
+    /// Thumbnails generated for every page chunk, as bounding square sizes
+    /// in pixels. Empty disables thumbnail generation.
+    pub thumbnail_sizes: Vec<u32>,
+    /// Store image files that browsers display natively (PNG, JPEG, GIF,
+    /// WebP) with their original bytes and mimetype instead of re-encoding
+    /// them as PNG. PDF pages are stored as `render.format`.
+    pub preserve_original_bytes: bool,
+    /// Store a perceptual hash and the ink coverage of every page in
+    /// `page_metadata.fingerprint`, used to find near-duplicate pages
+    pub fingerprint: bool,
+    /// What to do with pages whose ink coverage is below `blank_threshold`.
+    /// Anything but `keep` fingerprints pages even if `fingerprint` is off.
+    pub blank_pages: BlankPagePolicy,
+    /// Ink coverage (0.0-1.0) below which a page counts as blank
+    pub blank_threshold: f64,
+    /// PDF pages to import as ranges such as "1-10,25,40-"; all pages when
+    /// None. Pages keep their original page numbers.
+    pub pages: Option<String>,
+    /// Copy source files into the database's content store (`source_blob`),
+    /// so the document can be opened on machines without the original files
+    pub store_sources: bool,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: None,
+            render: RenderOptions::default(),
+            extract_text: false,
+            chunking: ChunkingStrategy::default(),
+            detect_regions: false,
+            on_duplicate: DuplicatePolicy::default(),
+            thumbnail_sizes: vec![128, 512],
+            preserve_original_bytes: false,
+            fingerprint: false,
+            blank_pages: BlankPagePolicy::default(),
+            blank_threshold: 0.002,
+            pages: None,
+            store_sources: false,
+        }
+    }
+}
+
+impl IngestOptions {
+    /// Effective number of render workers, always at least 1
+    pub fn render_concurrency(&self) -> usize {
+        self.concurrency.filter(|n| *n > 0).unwrap_or_else(|| {
+            std::thread::available_parallelism()
+                .map(|n| n.get())
+                .unwrap_or(1)
+        })
+    }
+
+    /// Whether pages need a fingerprint for these options
+    pub fn fingerprint_pages(&self) -> bool {
+        self.fingerprint || self.blank_pages != BlankPagePolicy::Keep
+    }
 }
@@ -38,14 +38,25 @@ pub fn run() {
             commands::get_document_with_pages,
             commands::get_pages,
             commands::get_page_chunks,
+            commands::get_page_text_chunks,
             commands::get_file_path,
             commands::get_document_page_count,
             commands::check_document_deletable,
             commands::delete_document,
+            commands::delete_page,
+            commands::reorder_pages,
+            commands::store_document_sources,
+            commands::relink_sources,
+            commands::verify_sources,
+            commands::find_near_duplicate_pages,
             // Image commands
             commands::get_source_file_url,
             commands::get_page_source_urls,
             commands::get_chunk_data_url,
+            commands::create_image_chunk_from_region,
+            commands::accept_auto_region,
+            commands::get_chunk_thumbnail_data_url,
+            commands::delete_image_chunk,
             // Query commands
             commands::create_query,
             commands::update_query,
@@ -59,8 +70,16 @@ pub fn run() {
             commands::update_retrieval_score,
             // Ingest commands
             commands::ingest_pdf,
+            commands::import_pdf_pages,
+            commands::rerender_document,
             commands::ingest_images,
+            commands::append_images,
+            commands::replace_page_image,
+            commands::ingest_directory,
+            commands::import_page_text,
             commands::get_supported_formats,
+            commands::cancel_ingestion,
+            commands::list_ingestion_jobs,
             // Export commands
             commands::get_export_counts,
             commands::export_data,
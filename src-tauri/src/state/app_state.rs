@@ -2,14 +2,18 @@ use sqlx::PgPool;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use super::jobs::JobRegistry;
+
 pub struct AppState {
     pub pool: Arc<RwLock<Option<PgPool>>>,
+    pub jobs: JobRegistry,
 }
 
 impl AppState {
     pub fn new() -> Self {
         Self {
             pool: Arc::new(RwLock::new(None)),
+            jobs: JobRegistry::default(),
         }
     }
 
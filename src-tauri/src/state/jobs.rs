@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::error::{AppError, Result};
+use crate::ingest::IngestionProgress;
+
+/// Snapshot of a running ingestion job, returned by `list_ingestion_jobs`
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestionJobInfo {
+    pub job_id: u64,
+    pub kind: String, // "pdf", "images", "directory"
+    pub source: String,
+    pub started_at: DateTime<Utc>,
+    pub progress: Option<IngestionProgress>,
+    pub cancel_requested: bool,
+}
+
+struct JobEntry {
+    info: IngestionJobInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Ingestion jobs currently running, keyed by job id
+#[derive(Clone, Default)]
+pub struct JobRegistry {
+    jobs: Arc<Mutex<HashMap<u64, JobEntry>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl JobRegistry {
+    /// Register a new job. It stays listed until every handle is dropped.
+    pub fn start(&self, kind: &str, source: &str) -> IngestionJob {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        let entry = JobEntry {
+            info: IngestionJobInfo {
+                job_id: id,
+                kind: kind.to_string(),
+                source: source.to_string(),
+                started_at: Utc::now(),
+                progress: None,
+                cancel_requested: false,
+            },
+            cancelled: cancelled.clone(),
+        };
+        self.lock().insert(id, entry);
+
+        IngestionJob(Arc::new(JobHandle {
+            id,
+            cancelled,
+            registry: self.clone(),
+        }))
+    }
+
+    /// Request cancellation of a job. Returns false if no such job is running.
+    pub fn cancel(&self, job_id: u64) -> bool {
+        match self.lock().get_mut(&job_id) {
+            Some(entry) => {
+                entry.cancelled.store(true, Ordering::Relaxed);
+                entry.info.cancel_requested = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Running jobs, oldest first
+    pub fn list(&self) -> Vec<IngestionJobInfo> {
+        let mut jobs: Vec<IngestionJobInfo> = self
+            .lock()
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect();
+        jobs.sort_by_key(|job| job.job_id);
+        jobs
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<u64, JobEntry>> {
+        // The map stays consistent even if a holder panicked
+        self.jobs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+struct JobHandle {
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+    registry: JobRegistry,
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        self.registry.lock().remove(&self.id);
+    }
+}
+
+/// Handle to a registered ingestion job. Cheap to clone so it can be moved
+/// into blocking tasks; the job is unregistered when the last clone drops.
+#[derive(Clone)]
+pub struct IngestionJob(Arc<JobHandle>);
+
+impl IngestionJob {
+    pub fn id(&self) -> u64 {
+        self.0.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Record progress for `list_ingestion_jobs` and emit it as `ingestion-progress`
+    pub fn report(&self, app_handle: &AppHandle, progress: IngestionProgress) {
+        let progress = IngestionProgress {
+            job_id: Some(self.id()),
+            ..progress
+        };
+
+        if let Some(entry) = self.0.registry.lock().get_mut(&self.id()) {
+            entry.info.progress = Some(progress.clone());
+        }
+        let _ = app_handle.emit("ingestion-progress", progress);
+    }
+
+    /// Fail with `AppError::Cancelled` once cancellation was requested,
+    /// emitting a final "Cancelled" progress event. Callers return the error
+    /// before committing, so the open transaction is rolled back.
+    pub fn ensure_active(&self, app_handle: &AppHandle) -> Result<()> {
+        if self.is_cancelled() {
+            self.report(app_handle, IngestionProgress::cancelled());
+            return Err(AppError::Cancelled(self.id()));
+        }
+        Ok(())
+    }
+}
@@ -1,3 +1,5 @@
 mod app_state;
+mod jobs;
 
 pub use app_state::AppState;
+pub use jobs::{IngestionJob, IngestionJobInfo};
@@ -0,0 +1,18 @@
+// Error returned by a failed Tauri command, serialized from the backend's AppError
+export interface AppError {
+  kind: string // e.g. 'pdf_password_required'; stable across message wording changes
+  message: string
+}
+
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === 'object' && err !== null && 'kind' in err && 'message' in err
+}
+
+export function errorMessage(err: unknown): string {
+  if (isAppError(err) || err instanceof Error) return err.message
+  return String(err)
+}
+
+export function errorKindOf(err: unknown): string | null {
+  return isAppError(err) ? err.kind : null
+}
@@ -3,6 +3,7 @@ import { acceptHMRUpdate, defineStore } from 'pinia'
 import { ref, computed } from 'vue'
 import { useSelectionStore } from './selection'
 import type { PageInfo, ImageChunkInfo } from './documents'
+import { errorMessage } from '@/lib/errors'
 
 // Types matching the new AutoRAG-Research schema
 export interface Query {
@@ -113,7 +114,7 @@ export const useAnnotationStore = defineStore('annotation', () => {
     try {
       queries.value = await invoke<Query[]>('list_queries')
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       queries.value = []
     } finally {
       isLoading.value = false
@@ -147,7 +148,7 @@ export const useAnnotationStore = defineStore('annotation', () => {
 
       return result
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       return null
     } finally {
       isSaving.value = false
@@ -182,7 +183,7 @@ export const useAnnotationStore = defineStore('annotation', () => {
 
       return result
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       return null
     } finally {
       isSaving.value = false
@@ -207,7 +208,7 @@ export const useAnnotationStore = defineStore('annotation', () => {
 
       return true
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       return false
     } finally {
       isSaving.value = false
@@ -218,7 +219,7 @@ export const useAnnotationStore = defineStore('annotation', () => {
     try {
       return await invoke<QueryWithEvidence>('get_query_with_evidence', { queryId })
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       return null
     }
   }
@@ -1,6 +1,7 @@
 import { invoke } from '@tauri-apps/api/core'
 import { acceptHMRUpdate, defineStore } from 'pinia'
 import { ref, computed } from 'vue'
+import { errorMessage } from '@/lib/errors'
 
 export interface DatabaseConfig {
   host: string
@@ -28,7 +29,7 @@ export const useConnectionStore = defineStore('connection', () => {
       isConnected.value = true
       return true
     } catch (error) {
-      connectionError.value = error instanceof Error ? error.message : String(error)
+      connectionError.value = errorMessage(error)
       return false
     } finally {
       isConnecting.value = false
@@ -43,7 +44,7 @@ export const useConnectionStore = defineStore('connection', () => {
       connectionError.value = null
       return true
     } catch (error) {
-      connectionError.value = error instanceof Error ? error.message : String(error)
+      connectionError.value = errorMessage(error)
       return false
     }
   }
@@ -53,7 +54,7 @@ export const useConnectionStore = defineStore('connection', () => {
       const result = await invoke<boolean>('test_connection')
       return result
     } catch (error) {
-      connectionError.value = error instanceof Error ? error.message : String(error)
+      connectionError.value = errorMessage(error)
       return false
     }
   }
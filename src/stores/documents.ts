@@ -2,6 +2,7 @@ import { invoke, convertFileSrc } from '@tauri-apps/api/core'
 import { acceptHMRUpdate, defineStore } from 'pinia'
 import { ref, computed } from 'vue'
 import type { Query } from './annotation'
+import { errorMessage } from '@/lib/errors'
 
 // Types matching the new AutoRAG-Research schema
 export interface File {
@@ -19,18 +20,31 @@ export interface Document {
   doc_metadata: Record<string, unknown> | null
 }
 
+// Stored image properties recorded at ingest (page_metadata.image / chunk_metadata.image)
+export interface ImageInfo {
+  width: number
+  height: number
+  dpi: number | null
+  byte_size: number
+  sha256: string
+  renderer: string | null
+}
+
 export interface PageInfo {
   id: number // bigserial
   page_num: number
   document_id: number
   mimetype: string | null
   page_metadata: Record<string, unknown> | null
+  image?: ImageInfo | null // null for pages ingested before image info was recorded
+  page_label?: string | null // logical label such as "iv" or "A-3"; null when the PDF defines none
 }
 
 export interface ImageChunkInfo {
   id: number // bigserial
   parent_page: number | null // FK to Page.id
   mimetype: string
+  image?: ImageInfo | null
 }
 
 export interface PageWithChunks {
@@ -48,6 +62,43 @@ export interface DocumentDeletionCheck {
   blocking_queries: Query[]
 }
 
+export interface SourceStoreReport {
+  stored: number
+  already_stored: number // content the store already held
+  missing: string[] // source paths not found on this machine
+  changed: string[] // source paths whose content changed since import
+  too_large: string[] // source paths of files too large for the content store
+}
+
+export interface RelinkReport {
+  files: number
+  pages: number // pages whose page_metadata.source_path changed
+  too_long: string[] // source paths left unchanged because the new path would not fit
+}
+
+export interface SourceProblem {
+  document_id: number
+  title: string | null
+  path: string
+  status: 'missing' | 'unreadable' | 'changed'
+  error: string | null // why the file could not be read, for 'unreadable'
+  in_content_store: boolean
+}
+
+export interface FingerprintedPage {
+  page_id: number
+  document_id: number
+  document_title: string | null
+  page_num: number
+  dhash: string
+}
+
+export interface NearDuplicatePages {
+  first: FingerprintedPage
+  second: FingerprintedPage
+  distance: number // differing perceptual hash bits
+}
+
 interface PageSourceInfo {
   page_id: number
   chunk_ids: number[]
@@ -86,7 +137,7 @@ export const useDocumentsStore = defineStore('documents', () => {
       documents.value = await invoke<Document[]>('list_documents')
     } catch (err) {
       console.error('loadDocuments error:', err)
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       documents.value = []
     } finally {
       isLoading.value = false
@@ -111,7 +162,7 @@ export const useDocumentsStore = defineStore('documents', () => {
         await loadPageSourceUrls(documentId)
       }
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       currentDocument.value = null
       sourceFilePath.value = null
     } finally {
@@ -145,6 +196,23 @@ export const useDocumentsStore = defineStore('documents', () => {
     }
   }
 
+  async function getChunkThumbnailDataUrl(chunkId: number, size: number): Promise<string | null> {
+    try {
+      return await invoke<string>('get_chunk_thumbnail_data_url', { chunkId, size })
+    } catch (err) {
+      console.error(`Failed to get thumbnail for chunk ${chunkId}:`, err)
+      return null
+    }
+  }
+
+  async function findNearDuplicatePages(
+    maxDistance?: number,
+    documentId?: number,
+    limit?: number
+  ): Promise<NearDuplicatePages[]> {
+    return await invoke<NearDuplicatePages[]>('find_near_duplicate_pages', { maxDistance, documentId, limit })
+  }
+
   async function checkDocumentDeletable(documentId: number): Promise<DocumentDeletionCheck> {
     return await invoke<DocumentDeletionCheck>('check_document_deletable', { documentId })
   }
@@ -160,13 +228,81 @@ export const useDocumentsStore = defineStore('documents', () => {
       return true
     } catch (err) {
       console.error('deleteDocument error:', err)
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       return false
     } finally {
       isDeleting.value = false
     }
   }
 
+  // Copy source files into the database's content store; all documents when documentId is omitted
+  async function storeDocumentSources(documentId?: number): Promise<SourceStoreReport | null> {
+    try {
+      return await invoke<SourceStoreReport>('store_document_sources', { documentId: documentId ?? null })
+    } catch (err) {
+      console.error('storeDocumentSources error:', err)
+      error.value = errorMessage(err)
+      return null
+    }
+  }
+
+  // Point sources under oldPrefix to newPrefix after the files were moved
+  async function relinkSources(oldPrefix: string, newPrefix: string): Promise<RelinkReport | null> {
+    try {
+      const report = await invoke<RelinkReport>('relink_sources', { oldPrefix, newPrefix })
+      if (currentDocument.value) {
+        await selectDocument(currentDocument.value.document.id)
+      }
+      return report
+    } catch (err) {
+      console.error('relinkSources error:', err)
+      error.value = errorMessage(err)
+      return null
+    }
+  }
+
+  // List sources that are missing or changed; all documents when documentId is omitted
+  async function verifySources(documentId?: number): Promise<SourceProblem[] | null> {
+    try {
+      return await invoke<SourceProblem[]>('verify_sources', { documentId: documentId ?? null })
+    } catch (err) {
+      console.error('verifySources error:', err)
+      error.value = errorMessage(err)
+      return null
+    }
+  }
+
+  // Delete a page of an image document; later pages move up one place
+  async function deletePage(pageId: number): Promise<boolean> {
+    try {
+      await invoke<boolean>('delete_page', { pageId })
+      const document = currentDocument.value?.document
+      if (document && currentDocument.value?.pages.some((p) => p.page.id === pageId)) {
+        await selectDocument(document.id)
+      }
+      return true
+    } catch (err) {
+      console.error('deletePage error:', err)
+      error.value = errorMessage(err)
+      return false
+    }
+  }
+
+  // Reorder the pages of an image document; pageIds lists every page in the new order
+  async function reorderPages(documentId: number, pageIds: number[]): Promise<PageInfo[] | null> {
+    try {
+      const pages = await invoke<PageInfo[]>('reorder_pages', { documentId, pageIds })
+      if (currentDocument.value?.document.id === documentId) {
+        await selectDocument(documentId)
+      }
+      return pages
+    } catch (err) {
+      console.error('reorderPages error:', err)
+      error.value = errorMessage(err)
+      return null
+    }
+  }
+
   function clearCurrentDocument() {
     currentDocument.value = null
     pageSourceUrls.value.clear()
@@ -189,8 +325,15 @@ export const useDocumentsStore = defineStore('documents', () => {
     selectDocument,
     getPageSourceUrl,
     getChunkDataUrl,
+    getChunkThumbnailDataUrl,
+    findNearDuplicatePages,
     checkDocumentDeletable,
     deleteDocument,
+    deletePage,
+    reorderPages,
+    storeDocumentSources,
+    relinkSources,
+    verifySources,
     clearCurrentDocument,
   }
 })
@@ -2,6 +2,7 @@ import { acceptHMRUpdate, defineStore } from 'pinia'
 import { ref, computed } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
 import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { errorMessage } from '@/lib/errors'
 
 export interface ExportConfig {
   output_path: string
@@ -87,7 +88,7 @@ export const useExportStore = defineStore('export', () => {
       counts.value = result
       return result
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       return null
     }
   }
@@ -106,7 +107,7 @@ export const useExportStore = defineStore('export', () => {
       lastResult.value = result
       return result
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
+      error.value = errorMessage(err)
       progress.value = {
         phase: 'Failed',
         current: 0,
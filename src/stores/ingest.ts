@@ -2,12 +2,55 @@ import { acceptHMRUpdate, defineStore } from 'pinia'
 import { ref, computed } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
 import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import type { PageInfo } from './documents'
+import { errorKindOf, errorMessage } from '@/lib/errors'
 
 export interface IngestionProgress {
   current_page: number
   total_pages: number
-  phase: 'Reading' | 'Rendering' | 'Complete' | 'Failed'
+  phase: 'Converting' | 'Reading' | 'Rendering' | 'Complete' | 'Failed' | 'Cancelled'
   message: string
+  job_id: number | null
+}
+
+export interface DirectoryScanOptions {
+  recursive?: boolean
+  include?: string[]
+  exclude?: string[]
+  image_per_document?: boolean
+}
+
+export interface BatchItemReport {
+  kind: 'pdf' | 'office' | 'images'
+  files: string[]
+  status: 'succeeded' | 'skipped' | 'failed'
+  result: IngestionResult | null
+  message: string | null
+}
+
+export interface DirectoryIngestionReport {
+  directory: string
+  items: BatchItemReport[]
+  succeeded: number
+  skipped: number
+  failed: number
+  cancelled: boolean
+}
+
+export interface IngestionJobInfo {
+  job_id: number
+  kind: 'pdf' | 'images' | 'directory'
+  source: string
+  started_at: string
+  progress: IngestionProgress | null
+  cancel_requested: boolean
+}
+
+// File extensions accepted for ingestion, grouped by how they are imported
+export interface SupportedFormats {
+  pdf: string[]
+  office: string[] // converted to PDF with LibreOffice
+  images: string[]
 }
 
 export interface IngestionResult {
@@ -15,12 +58,59 @@ export interface IngestionResult {
   document_id: number
   page_count: number
   image_chunk_count: number
+  chunk_count: number
+  duplicate_of: number | null
+  blank_pages: number[] // page numbers detected as blank (flagged or skipped)
+}
+
+export interface RerenderResult {
+  document_id: number
+  page_count: number
+  image_chunk_count: number // page and region chunks whose contents were replaced
+}
+
+export interface RenderOptions {
+  dpi?: number
+  format?: 'png' | 'jpeg'
+  jpeg_quality?: number
+  grayscale?: boolean
+  max_dimension?: number | null
+}
+
+export type ChunkingStrategy =
+  | { strategy: 'page' }
+  | { strategy: 'fixed_tokens'; size: number; overlap: number }
+  | { strategy: 'sentence'; max_tokens: number }
+  | { strategy: 'paragraph'; max_tokens: number }
+
+export type DuplicatePolicy = 'refuse' | 'link' | 'force'
+
+export type BlankPagePolicy = 'keep' | 'flag' | 'skip'
+
+// Why an encrypted PDF could not be opened, matching the PdfPasswordRequired and PdfWrongPassword errors
+export type PdfPasswordError = 'required' | 'wrong'
+
+export interface IngestOptions {
+  concurrency?: number | null
+  render?: RenderOptions
+  extract_text?: boolean
+  chunking?: ChunkingStrategy
+  detect_regions?: boolean
+  on_duplicate?: DuplicatePolicy
+  thumbnail_sizes?: number[]
+  preserve_original_bytes?: boolean
+  fingerprint?: boolean
+  blank_pages?: BlankPagePolicy
+  blank_threshold?: number
+  pages?: string | null // PDF page ranges such as "1-10,25,40-"; all pages when null
+  store_sources?: boolean // copy source files into the database so other machines can open them
 }
 
 export const useIngestStore = defineStore('ingest', () => {
   const isIngesting = ref(false)
   const progress = ref<IngestionProgress | null>(null)
   const error = ref<string | null>(null)
+  const errorKind = ref<string | null>(null) // AppError kind behind `error`, null when it came from a progress event
   const lastResult = ref<IngestionResult | null>(null)
 
   let unlistenProgress: UnlistenFn | null = null
@@ -32,6 +122,13 @@ export const useIngestStore = defineStore('ingest', () => {
 
   const isComplete = computed(() => progress.value?.phase === 'Complete')
   const isFailed = computed(() => progress.value?.phase === 'Failed')
+  const isCancelled = computed(() => progress.value?.phase === 'Cancelled')
+  const currentJobId = computed(() => progress.value?.job_id ?? null)
+  const passwordError = computed<PdfPasswordError | null>(() => {
+    if (errorKind.value === 'pdf_password_required') return 'required'
+    if (errorKind.value === 'pdf_wrong_password') return 'wrong'
+    return null
+  })
 
   async function startListening() {
     if (unlistenProgress) {
@@ -53,10 +150,17 @@ export const useIngestStore = defineStore('ingest', () => {
     }
   }
 
-  async function ingestPdf(filePath: string, title?: string, author?: string): Promise<IngestionResult | null> {
+  async function ingestPdf(
+    filePath: string,
+    title?: string,
+    author?: string,
+    password?: string,
+    options?: IngestOptions
+  ): Promise<IngestionResult | null> {
     isIngesting.value = true
     progress.value = null
     error.value = null
+    errorKind.value = null
     lastResult.value = null
 
     try {
@@ -66,17 +170,23 @@ export const useIngestStore = defineStore('ingest', () => {
         filePath,
         title: title || null,
         author: author || null,
+        password: password || null,
+        options: options ?? null,
       })
 
       lastResult.value = result
       return result
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
-      progress.value = {
-        current_page: 0,
-        total_pages: 0,
-        phase: 'Failed',
-        message: error.value,
+      error.value = errorMessage(err)
+      errorKind.value = errorKindOf(err)
+      if (!isCancelled.value) {
+        progress.value = {
+          current_page: 0,
+          total_pages: 0,
+          phase: 'Failed',
+          message: error.value,
+          job_id: null,
+        }
       }
       return null
     } finally {
@@ -85,10 +195,15 @@ export const useIngestStore = defineStore('ingest', () => {
     }
   }
 
-  async function ingestImages(filePaths: string[], title: string): Promise<IngestionResult | null> {
+  async function ingestImages(
+    filePaths: string[],
+    title: string,
+    options?: IngestOptions
+  ): Promise<IngestionResult | null> {
     isIngesting.value = true
     progress.value = null
     error.value = null
+    errorKind.value = null
     lastResult.value = null
 
     try {
@@ -97,17 +212,22 @@ export const useIngestStore = defineStore('ingest', () => {
       const result = await invoke<IngestionResult>('ingest_images', {
         filePaths,
         title,
+        options: options ?? null,
       })
 
       lastResult.value = result
       return result
     } catch (err) {
-      error.value = err instanceof Error ? err.message : String(err)
-      progress.value = {
-        current_page: 0,
-        total_pages: 0,
-        phase: 'Failed',
-        message: error.value,
+      error.value = errorMessage(err)
+      errorKind.value = errorKindOf(err)
+      if (!isCancelled.value) {
+        progress.value = {
+          current_page: 0,
+          total_pages: 0,
+          phase: 'Failed',
+          message: error.value,
+          job_id: null,
+        }
       }
       return null
     } finally {
@@ -116,11 +236,181 @@ export const useIngestStore = defineStore('ingest', () => {
     }
   }
 
-  async function getSupportedFormats(): Promise<string[]> {
+  async function appendImages(
+    documentId: number,
+    filePaths: string[],
+    options?: IngestOptions
+  ): Promise<IngestionResult | null> {
+    isIngesting.value = true
+    progress.value = null
+    error.value = null
+    errorKind.value = null
+    lastResult.value = null
+
+    try {
+      await startListening()
+
+      const result = await invoke<IngestionResult>('append_images', {
+        documentId,
+        filePaths,
+        options: options ?? null,
+      })
+
+      lastResult.value = result
+      return result
+    } catch (err) {
+      error.value = errorMessage(err)
+      errorKind.value = errorKindOf(err)
+      return null
+    } finally {
+      isIngesting.value = false
+      stopListening()
+    }
+  }
+
+  async function replacePageImage(
+    pageId: number,
+    filePath: string,
+    options?: IngestOptions
+  ): Promise<PageInfo | null> {
+    isIngesting.value = true
+    progress.value = null
+    error.value = null
+    errorKind.value = null
+
+    try {
+      await startListening()
+
+      return await invoke<PageInfo>('replace_page_image', {
+        pageId,
+        filePath,
+        options: options ?? null,
+      })
+    } catch (err) {
+      error.value = errorMessage(err)
+      errorKind.value = errorKindOf(err)
+      return null
+    } finally {
+      isIngesting.value = false
+      stopListening()
+    }
+  }
+
+  async function importPdfPages(
+    documentId: number,
+    pages: string,
+    password?: string,
+    options?: IngestOptions
+  ): Promise<IngestionResult | null> {
+    isIngesting.value = true
+    progress.value = null
+    error.value = null
+    errorKind.value = null
+    lastResult.value = null
+
+    try {
+      await startListening()
+
+      const result = await invoke<IngestionResult>('import_pdf_pages', {
+        documentId,
+        pages,
+        password: password || null,
+        options: options ?? null,
+      })
+
+      lastResult.value = result
+      return result
+    } catch (err) {
+      error.value = errorMessage(err)
+      errorKind.value = errorKindOf(err)
+      return null
+    } finally {
+      isIngesting.value = false
+      stopListening()
+    }
+  }
+
+  async function rerenderDocument(
+    documentId: number,
+    options: RenderOptions,
+    password?: string
+  ): Promise<RerenderResult | null> {
+    isIngesting.value = true
+    progress.value = null
+    error.value = null
+    errorKind.value = null
+
+    try {
+      await startListening()
+
+      return await invoke<RerenderResult>('rerender_document', {
+        documentId,
+        password: password || null,
+        options,
+      })
+    } catch (err) {
+      error.value = errorMessage(err)
+      errorKind.value = errorKindOf(err)
+      return null
+    } finally {
+      isIngesting.value = false
+      stopListening()
+    }
+  }
+
+  async function ingestDirectory(
+    directory: string,
+    scan?: DirectoryScanOptions,
+    options?: IngestOptions
+  ): Promise<DirectoryIngestionReport | null> {
+    isIngesting.value = true
+    progress.value = null
+    error.value = null
+    errorKind.value = null
+    lastResult.value = null
+
+    try {
+      await startListening()
+
+      return await invoke<DirectoryIngestionReport>('ingest_directory', {
+        directory,
+        scan: scan ?? null,
+        options: options ?? null,
+      })
+    } catch (err) {
+      error.value = errorMessage(err)
+      errorKind.value = errorKindOf(err)
+      return null
+    } finally {
+      isIngesting.value = false
+      stopListening()
+    }
+  }
+
+  async function cancelIngestion(): Promise<boolean> {
+    if (currentJobId.value === null) return false
+    try {
+      return await invoke<boolean>('cancel_ingestion', { jobId: currentJobId.value })
+    } catch (err) {
+      console.error('Failed to cancel ingestion:', err)
+      return false
+    }
+  }
+
+  async function listIngestionJobs(): Promise<IngestionJobInfo[]> {
+    try {
+      return await invoke<IngestionJobInfo[]>('list_ingestion_jobs')
+    } catch (err) {
+      console.error('Failed to list ingestion jobs:', err)
+      return []
+    }
+  }
+
+  async function getSupportedFormats(): Promise<SupportedFormats> {
     try {
-      return await invoke<string[]>('get_supported_formats')
+      return await invoke<SupportedFormats>('get_supported_formats')
     } catch {
-      return ['pdf']
+      return { pdf: ['pdf'], office: [], images: [] }
     }
   }
 
@@ -128,6 +418,7 @@ export const useIngestStore = defineStore('ingest', () => {
     isIngesting.value = false
     progress.value = null
     error.value = null
+    errorKind.value = null
     lastResult.value = null
   }
 
@@ -135,12 +426,23 @@ export const useIngestStore = defineStore('ingest', () => {
     isIngesting,
     progress,
     error,
+    errorKind,
     lastResult,
     progressPercent,
     isComplete,
     isFailed,
+    isCancelled,
+    currentJobId,
+    passwordError,
     ingestPdf,
     ingestImages,
+    appendImages,
+    replacePageImage,
+    importPdfPages,
+    rerenderDocument,
+    ingestDirectory,
+    cancelIngestion,
+    listIngestionJobs,
     getSupportedFormats,
     reset,
   }
@@ -20,7 +20,7 @@ interface EvidenceGroupLike {
   group_index: number
   items: {
     relation?: { score: number } | null
-    chunk?: { id: number } | null
+    chunk?: { id: number; mimetype?: string } | null
     page?: { id: number; page_num: number; document_id: number; mimetype: string | null } | null
   }[]
 }
@@ -298,7 +298,7 @@ export const useSelectionStore = defineStore('selection', () => {
                 chunks.push({
                   id: groupItem.chunk.id,
                   parent_page: pageId,
-                  mimetype: 'image/png', // default, actual mimetype comes from chunk data
+                  mimetype: groupItem.chunk.mimetype ?? 'image/png',
                 })
               }
             }